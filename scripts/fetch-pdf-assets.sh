@@ -0,0 +1,92 @@
+#!/bin/bash
+
+# Fetch the fonts and highlight.js files bundled into PDF export
+# Usage: ./scripts/fetch-pdf-assets.sh [--force]
+#
+# The files are compiled into the binary so exports render the same offline.
+# Runs automatically before `tauri dev`/`tauri build` (pnpm pdf-assets) and in
+# the release workflow. Files already present are kept; pass --force to
+# download them again after bumping the versions below.
+
+set -e
+
+HLJS_VERSION="11.9.0"
+FONTSOURCE_VERSION="5"
+KATEX_VERSION="0.16.25"
+
+SCRIPT_DIR="$(cd "$(dirname "${BASH_SOURCE[0]}")" && pwd)"
+PROJECT_DIR="$(dirname "$SCRIPT_DIR")"
+ASSETS_DIR="$PROJECT_DIR/src-tauri/assets/pdf"
+
+HLJS_URL="https://cdnjs.cloudflare.com/ajax/libs/highlight.js/$HLJS_VERSION"
+FONT_URL="https://cdn.jsdelivr.net/npm/@fontsource/libre-baskerville@$FONTSOURCE_VERSION/files"
+KATEX_URL="https://cdn.jsdelivr.net/npm/katex@$KATEX_VERSION/dist"
+# The frontend's locked katex package, preferred over the CDN so PDF math
+# matches the reader and needs no extra download
+KATEX_DIST="$PROJECT_DIR/node_modules/katex/dist"
+
+KATEX_FONTS=(
+    KaTeX_AMS-Regular
+    KaTeX_Caligraphic-Bold
+    KaTeX_Caligraphic-Regular
+    KaTeX_Fraktur-Bold
+    KaTeX_Fraktur-Regular
+    KaTeX_Main-Bold
+    KaTeX_Main-BoldItalic
+    KaTeX_Main-Italic
+    KaTeX_Main-Regular
+    KaTeX_Math-BoldItalic
+    KaTeX_Math-Italic
+    KaTeX_SansSerif-Bold
+    KaTeX_SansSerif-Italic
+    KaTeX_SansSerif-Regular
+    KaTeX_Script-Regular
+    KaTeX_Size1-Regular
+    KaTeX_Size2-Regular
+    KaTeX_Size3-Regular
+    KaTeX_Size4-Regular
+    KaTeX_Typewriter-Regular
+)
+
+FORCE=false
+if [ "$1" = "--force" ]; then
+    FORCE=true
+fi
+
+mkdir -p "$ASSETS_DIR/katex/fonts"
+
+fetch() {
+    if [ "$FORCE" = false ] && [ -s "$ASSETS_DIR/$2" ]; then
+        return
+    fi
+    echo "Fetching $2"
+    # Download to a temp file so an interrupted fetch never leaves a partial asset
+    curl -fsSL "$1" -o "$ASSETS_DIR/$2.tmp"
+    mv "$ASSETS_DIR/$2.tmp" "$ASSETS_DIR/$2"
+}
+
+fetch "$HLJS_URL/highlight.min.js" "highlight.min.js"
+fetch "$HLJS_URL/styles/github-dark.min.css" "github-dark.min.css"
+fetch "$FONT_URL/libre-baskerville-latin-400-normal.woff2" "libre-baskerville-400.woff2"
+fetch "$FONT_URL/libre-baskerville-latin-400-italic.woff2" "libre-baskerville-400-italic.woff2"
+fetch "$FONT_URL/libre-baskerville-latin-700-normal.woff2" "libre-baskerville-700.woff2"
+# Copy a KaTeX file from node_modules when installed, otherwise download it
+katex() {
+    if [ "$FORCE" = false ] && [ -s "$ASSETS_DIR/katex/$1" ]; then
+        return
+    fi
+    if [ -s "$KATEX_DIST/$1" ]; then
+        echo "Copying katex/$1"
+        cp "$KATEX_DIST/$1" "$ASSETS_DIR/katex/$1"
+    else
+        fetch "$KATEX_URL/$1" "katex/$1"
+    fi
+}
+
+katex "katex.min.js"
+katex "katex.min.css"
+for font in "${KATEX_FONTS[@]}"; do
+    katex "fonts/$font.woff2"
+done
+
+echo "PDF assets saved to $ASSETS_DIR"
@@ -1,3 +1,56 @@
+use std::path::Path;
+
+/// Files compiled into PDF export so it renders without network access
+const PDF_ASSETS: &[&str] = &[
+    "highlight.min.js",
+    "github-dark.min.css",
+    "libre-baskerville-400.woff2",
+    "libre-baskerville-400-italic.woff2",
+    "libre-baskerville-700.woff2",
+    "katex/katex.min.js",
+    "katex/katex.min.css",
+];
+
+/// KaTeX fonts, expected as `assets/pdf/katex/fonts/<name>.woff2`
+const KATEX_FONTS: &[&str] = &[
+    "KaTeX_AMS-Regular",
+    "KaTeX_Caligraphic-Bold",
+    "KaTeX_Caligraphic-Regular",
+    "KaTeX_Fraktur-Bold",
+    "KaTeX_Fraktur-Regular",
+    "KaTeX_Main-Bold",
+    "KaTeX_Main-BoldItalic",
+    "KaTeX_Main-Italic",
+    "KaTeX_Main-Regular",
+    "KaTeX_Math-BoldItalic",
+    "KaTeX_Math-Italic",
+    "KaTeX_SansSerif-Bold",
+    "KaTeX_SansSerif-Italic",
+    "KaTeX_SansSerif-Regular",
+    "KaTeX_Script-Regular",
+    "KaTeX_Size1-Regular",
+    "KaTeX_Size2-Regular",
+    "KaTeX_Size3-Regular",
+    "KaTeX_Size4-Regular",
+    "KaTeX_Typewriter-Regular",
+];
+
 fn main() {
+    println!("cargo:rerun-if-changed=assets/pdf");
+
+    let katex_fonts = KATEX_FONTS.iter().map(|name| format!("katex/fonts/{}.woff2", name));
+    let missing: Vec<String> = PDF_ASSETS
+        .iter()
+        .map(|name| name.to_string())
+        .chain(katex_fonts)
+        .filter(|name| !Path::new("assets/pdf").join(name).is_file())
+        .collect();
+    if !missing.is_empty() {
+        panic!(
+            "Missing PDF export assets in src-tauri/assets/pdf: {}. Run `pnpm pdf-assets` (scripts/fetch-pdf-assets.sh) to download them.",
+            missing.join(", ")
+        );
+    }
+
     tauri_build::build()
 }
@@ -1,13 +1,136 @@
-use crate::models::{ProjectMeta, SelectionRange, ExpansionResult};
-use crate::services::{ai_service, config_service};
+use crate::models::{
+    ProjectMeta, SelectionRange, ExpansionResult, ContextEstimate, ObjectiveCoverage, CompletenessAssessment,
+    Quiz, Depth, ExpansionRecord, GlossaryEntry
+};
+use crate::services::{ai_service, config_service, file_service};
 use tauri::AppHandle;
 
 #[tauri::command]
-pub async fn generate_learning(app: AppHandle, topic: String, depth: String) -> Result<ProjectMeta, String> {
+pub async fn generate_learning(
+    app: AppHandle,
+    topic: String,
+    depth: String,
+    plan_then_write: Option<bool>,
+    language: Option<String>,
+    target_words_per_chapter: Option<usize>,
+) -> Result<ProjectMeta, String> {
+    let depth: Depth = depth.parse()?;
     let api_key = config_service::get_api_key()?
         .ok_or("API key not configured")?;
 
-    ai_service::generate_learning_material(&topic, &depth, &api_key, app).await
+    let language = language
+        .map(|l| l.trim().to_string())
+        .filter(|l| !l.is_empty());
+    let target_words_per_chapter = target_words_per_chapter.filter(|n| *n > 0);
+
+    if plan_then_write.unwrap_or(false) {
+        return ai_service::generate_outlined_material(
+            &topic,
+            depth,
+            language.as_deref(),
+            target_words_per_chapter,
+            app,
+        ).await;
+    }
+
+    ai_service::generate_learning_material(
+        &topic,
+        depth,
+        language.as_deref(),
+        target_words_per_chapter,
+        &api_key,
+        app,
+    ).await
+}
+
+#[tauri::command]
+pub async fn generate_from_objectives(
+    app: AppHandle,
+    topic: String,
+    objectives: Vec<String>,
+    depth: String,
+) -> Result<ProjectMeta, String> {
+    let depth: Depth = depth.parse()?;
+    let api_key = config_service::get_api_key()?
+        .ok_or("API key not configured")?;
+
+    let objectives: Vec<String> = objectives.into_iter()
+        .map(|o| o.trim().to_string())
+        .filter(|o| !o.is_empty())
+        .collect();
+    if objectives.is_empty() {
+        return Err("Provide at least one learning objective".to_string());
+    }
+
+    ai_service::generate_from_objectives(&topic, &objectives, depth, &api_key, app).await
+}
+
+#[tauri::command]
+pub async fn resume_generation(app: AppHandle, project_id: String) -> Result<ProjectMeta, String> {
+    ai_service::resume_generation(&project_id, app).await
+}
+
+#[tauri::command]
+pub fn cancel_generation(project_id: String) -> Result<(), String> {
+    ai_service::cancel_generation(&project_id)
+}
+
+#[tauri::command]
+pub async fn regenerate_page(
+    app: AppHandle,
+    project_id: String,
+    page_name: String,
+    instructions: String,
+) -> Result<String, String> {
+    ai_service::regenerate_page(&project_id, &page_name, &instructions, app).await
+}
+
+#[tauri::command]
+pub async fn continue_page(
+    app: AppHandle,
+    project_id: String,
+    page_name: String,
+    guidance: String,
+) -> Result<String, String> {
+    ai_service::continue_page(&project_id, &page_name, &guidance, app).await
+}
+
+#[tauri::command]
+pub async fn generate_quiz(project_id: String, page_name: String, num_questions: usize) -> Result<Quiz, String> {
+    ai_service::generate_quiz(&project_id, &page_name, num_questions).await
+}
+
+#[tauri::command]
+pub async fn generate_glossary(project_id: String) -> Result<Vec<GlossaryEntry>, String> {
+    ai_service::generate_glossary(&project_id).await
+}
+
+#[tauri::command]
+pub async fn verify_objectives_covered(project_id: String) -> Result<Vec<ObjectiveCoverage>, String> {
+    ai_service::verify_objectives_covered(&project_id).await
+}
+
+#[tauri::command]
+pub async fn assess_completeness(project_id: String) -> Result<CompletenessAssessment, String> {
+    ai_service::assess_completeness(&project_id).await
+}
+
+#[tauri::command]
+pub async fn generate_series(
+    app: AppHandle,
+    series_title: String,
+    topics: Vec<String>,
+    depth: String,
+) -> Result<Vec<ProjectMeta>, String> {
+    let depth: Depth = depth.parse()?;
+    let api_key = config_service::get_api_key()?
+        .ok_or("API key not configured")?;
+
+    if topics.is_empty() {
+        return Err("A series needs at least one module topic".to_string());
+    }
+
+    ai_service::generate_series(&series_title, &topics, depth, &api_key, app).await
 }
 
 #[tauri::command]
@@ -16,10 +139,16 @@ pub async fn expand_selection(
     page_name: String,
     selection: SelectionRange,
     question: String,
+    apply: Option<bool>,
 ) -> Result<ExpansionResult, String> {
     let api_key = config_service::get_api_key()?
         .ok_or("API key not configured")?;
 
+    // A dry run is held for review; accept_expansion saves it later
+    if !apply.unwrap_or(true) {
+        return ai_service::preview_expansion(&project_id, &page_name, &selection, &question).await;
+    }
+
     ai_service::expand_selection_with_ai(
         &project_id,
         &page_name,
@@ -29,33 +158,68 @@ pub async fn expand_selection(
     ).await
 }
 
+#[tauri::command]
+pub async fn preview_expansion(
+    project_id: String,
+    page_name: String,
+    selection: SelectionRange,
+    question: String,
+) -> Result<ExpansionResult, String> {
+    config_service::get_api_key()?
+        .ok_or("API key not configured")?;
+
+    ai_service::preview_expansion(&project_id, &page_name, &selection, &question).await
+}
+
+#[tauri::command]
+pub fn accept_expansion(expansion_id: String) -> Result<ExpansionResult, String> {
+    ai_service::accept_expansion(&expansion_id)
+}
+
+#[tauri::command]
+pub fn reject_expansion(expansion_id: String) -> Result<(), String> {
+    ai_service::reject_expansion(&expansion_id)
+}
+
+#[tauri::command]
+pub fn estimate_expansion_context(
+    project_id: String,
+    page_name: String,
+    selection: SelectionRange,
+    question: String,
+) -> Result<ContextEstimate, String> {
+    ai_service::estimate_expansion_context(&project_id, &page_name, &selection, &question)
+}
+
 #[tauri::command]
 pub fn remove_expansion(
     project_id: String,
     page_name: String,
     expansion_id: String,
 ) -> Result<String, String> {
-    use crate::services::file_service::{load_page_content, save_page_content};
-
-    let content = load_page_content(&project_id, &page_name)?;
-
-    // Remove the expansion block by finding and removing the details element
-    let pattern = format!(r#"<details class="ai-expansion" data-expansion-id="{}".*?</details>"#, expansion_id);
-    let re = regex::Regex::new(&pattern).map_err(|e| e.to_string())?;
-    let updated = re.replace(&content, "").to_string();
+    file_service::remove_expansion(&project_id, &page_name, &expansion_id)
+}
 
-    save_page_content(&project_id, &page_name, &updated)?;
+#[tauri::command]
+pub fn undo_last_expansion(project_id: String, page_name: String) -> Result<String, String> {
+    file_service::undo_last_expansion(&project_id, &page_name)
+}
 
-    Ok(updated)
+#[tauri::command]
+pub fn list_expansions(project_id: String) -> Result<Vec<ExpansionRecord>, String> {
+    file_service::list_expansions(&project_id)
 }
 
 #[tauri::command]
 pub async fn answer_question(
     selection: SelectionRange,
     question: String,
+    project_id: Option<String>,
+    page_name: Option<String>,
 ) -> Result<String, String> {
     let api_key = config_service::get_api_key()?
         .ok_or("API key not configured")?;
 
-    ai_service::answer_question(&selection, &question, &api_key).await
+    let page = project_id.as_deref().zip(page_name.as_deref());
+    ai_service::answer_question(&selection, &question, page, &api_key).await
 }
@@ -0,0 +1,22 @@
+use crate::models::ProjectMeta;
+use crate::services::backup_service;
+
+#[tauri::command]
+pub fn create_backup(output_path: String, include_api_key: Option<bool>) -> Result<(), String> {
+    backup_service::create_backup(&output_path, include_api_key.unwrap_or(false))
+}
+
+#[tauri::command]
+pub fn restore_backup(backup_path: String) -> Result<(), String> {
+    backup_service::restore_backup(&backup_path)
+}
+
+#[tauri::command]
+pub fn export_project_bundle(project_id: String, output_path: String) -> Result<(), String> {
+    backup_service::export_project_bundle(&project_id, &output_path)
+}
+
+#[tauri::command]
+pub fn import_project_bundle(zip_path: String) -> Result<ProjectMeta, String> {
+    backup_service::import_project_bundle(&zip_path)
+}
@@ -1,4 +1,4 @@
-use crate::models::{ChatSession, ChatSessionListItem};
+use crate::models::{ChatSession, ChatSessionListItem, ToolAction, ChatRevertSummary};
 use crate::services::{file_service, ai_service, config_service};
 use tauri::AppHandle;
 
@@ -17,6 +17,21 @@ pub fn get_chat_session(project_id: String, session_id: String) -> Result<ChatSe
     file_service::load_chat_session(&project_id, &session_id)
 }
 
+#[tauri::command]
+pub fn get_session_actions(project_id: String, session_id: String) -> Result<Vec<ToolAction>, String> {
+    Ok(file_service::load_chat_session(&project_id, &session_id)?.tool_actions)
+}
+
+#[tauri::command]
+pub fn revert_chat_changes(project_id: String, session_id: String) -> Result<ChatRevertSummary, String> {
+    ai_service::revert_chat_changes(&project_id, &session_id)
+}
+
+#[tauri::command]
+pub fn close_chat_session(project_id: String, session_id: String) -> Result<(), String> {
+    file_service::flush_chat_session(&project_id, &session_id)
+}
+
 #[tauri::command]
 pub fn delete_chat_session(project_id: String, session_id: String) -> Result<(), String> {
     file_service::delete_chat_session(&project_id, &session_id)
@@ -40,3 +55,8 @@ pub async fn send_chat_message(
         app,
     ).await
 }
+
+#[tauri::command]
+pub fn cancel_chat(session_id: String) -> Result<(), String> {
+    ai_service::cancel_chat(&session_id)
+}
@@ -1,4 +1,5 @@
-use crate::services::config_service;
+use crate::services::{config_service, file_service};
+use crate::services::config_service::ProviderPreset;
 
 #[tauri::command]
 pub fn get_api_key() -> Result<Option<String>, String> {
@@ -20,6 +21,11 @@ pub fn set_base_url(url: String) -> Result<(), String> {
     config_service::set_base_url(&url)
 }
 
+#[tauri::command]
+pub async fn test_connection() -> Result<(), String> {
+    config_service::test_connection().await
+}
+
 #[tauri::command]
 pub fn get_model() -> Result<Option<String>, String> {
     config_service::get_model()
@@ -40,7 +46,92 @@ pub fn set_provider(provider: String) -> Result<(), String> {
     config_service::set_provider(&provider)
 }
 
+#[tauri::command]
+pub fn provider_presets() -> Vec<ProviderPreset> {
+    config_service::PROVIDER_PRESETS.to_vec()
+}
+
+#[tauri::command]
+pub fn apply_provider_preset(id: String) -> Result<(), String> {
+    config_service::apply_provider_preset(&id)
+}
+
+#[tauri::command]
+pub fn set_chat_autosave(interval_secs: Option<u64>, batch_size: Option<usize>) -> Result<(), String> {
+    config_service::set_chat_autosave(interval_secs, batch_size)
+}
+
+#[tauri::command]
+pub fn set_extra_params(params: serde_json::Map<String, serde_json::Value>) -> Result<(), String> {
+    config_service::set_extra_params(params)
+}
+
+#[tauri::command]
+pub fn get_context_limit() -> Result<usize, String> {
+    config_service::get_context_limit()
+}
+
+#[tauri::command]
+pub fn set_context_limit(tokens: Option<usize>) -> Result<(), String> {
+    config_service::set_context_limit(tokens)
+}
+
+#[tauri::command]
+pub fn set_web_search(enabled: bool) -> Result<(), String> {
+    config_service::set_web_search(enabled)
+}
+
+#[tauri::command]
+pub fn set_fallback_model(model: Option<String>, after_failures: Option<u32>) -> Result<(), String> {
+    config_service::set_fallback_model(model, after_failures)
+}
+
+#[tauri::command]
+pub fn set_max_iterations(generation: Option<u32>, chat: Option<u32>) -> Result<(), String> {
+    config_service::set_max_iterations(generation, chat)
+}
+
+#[tauri::command]
+pub fn set_sampling_defaults(max_tokens: Option<u32>, top_p: Option<f32>) -> Result<(), String> {
+    config_service::set_sampling_defaults(max_tokens, top_p)
+}
+
+#[tauri::command]
+pub fn set_temperature(temperature: Option<f32>) -> Result<(), String> {
+    config_service::set_temperature(temperature)
+}
+
+#[tauri::command]
+pub fn set_generation_seed(seed: Option<u64>) -> Result<(), String> {
+    config_service::set_generation_seed(seed)
+}
+
+#[tauri::command]
+pub fn set_request_timeout(secs: Option<u64>) -> Result<(), String> {
+    config_service::set_request_timeout(secs)
+}
+
+#[tauri::command]
+pub fn set_proxy(http_proxy: Option<String>, https_proxy: Option<String>) -> Result<(), String> {
+    config_service::set_proxy(http_proxy, https_proxy)
+}
+
 #[tauri::command]
 pub fn get_config() -> Result<config_service::Config, String> {
     config_service::get_full_config()
 }
+
+#[tauri::command]
+pub fn get_storage_paths() -> Result<file_service::StoragePaths, String> {
+    file_service::get_storage_paths()
+}
+
+#[tauri::command]
+pub fn get_data_dir() -> Result<String, String> {
+    file_service::get_data_dir()
+}
+
+#[tauri::command]
+pub fn open_data_dir() -> Result<(), String> {
+    file_service::open_data_dir()
+}
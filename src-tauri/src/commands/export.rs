@@ -1,30 +1,84 @@
-use crate::services::file_service::{load_project, load_page_content, get_app_data_dir};
-use crate::services::pdf_service::export_project_to_pdf;
+use crate::services::file_service::{self, load_project, load_page_content};
+use crate::services::pdf_service::{export_project_to_pdf, export_project_to_html, cleanup_temp_exports, PdfOptions};
 
 #[tauri::command]
-pub async fn export_to_pdf(project_id: String, output_path: String) -> Result<(), String> {
+pub async fn export_to_pdf(
+    project_id: String,
+    output_path: String,
+    options: Option<PdfOptions>,
+    page_names: Option<Vec<String>>,
+) -> Result<(), String> {
     // Load project metadata
     let project = load_project(&project_id)?;
 
-    // Load all page contents
+    // Export only the requested pages when given, kept in book order
+    let page_order: Vec<&String> = match &page_names {
+        Some(names) => {
+            if let Some(missing) = names.iter().find(|n| !project.page_order.contains(n)) {
+                return Err(format!("Page '{}' not found in project", missing));
+            }
+            project.page_order.iter().filter(|p| names.contains(p)).collect()
+        }
+        None => project.page_order.iter().collect(),
+    };
+    if page_names.is_some() && page_order.is_empty() {
+        return Err("No pages selected for export".to_string());
+    }
+
+    let mut options = options.unwrap_or_default();
+
+    // Clean exports are a subscriber perk. Subscription state isn't tracked by
+    // the app yet, so every export keeps the watermark for now.
+    options.watermark = true;
+
+    // Load the page contents, plus each page's notes when they're printed
     let mut pages = Vec::new();
-    for page_name in &project.page_order {
+    let mut annotations = Vec::new();
+    for page_name in page_order {
         let content = load_page_content(&project_id, page_name)?;
         pages.push(content);
+        if options.include_annotations {
+            annotations.push(file_service::list_annotations(&project_id, Some(page_name))?);
+        }
     }
 
     // Export directly to the user-selected path
-    export_project_to_pdf(&project.title, pages, &output_path)?;
+    export_project_to_pdf(
+        &project.title,
+        pages,
+        &annotations,
+        &output_path,
+        &options,
+        project.cover_image.as_deref(),
+    )?;
 
     Ok(())
 }
 
 #[tauri::command]
-pub fn get_exports_dir() -> Result<String, String> {
-    let exports_dir = get_app_data_dir()?.join("exports");
-    if !exports_dir.exists() {
-        std::fs::create_dir_all(&exports_dir)
-            .map_err(|e| format!("Failed to create exports directory: {}", e))?;
+pub async fn export_to_html(project_id: String, output_path: String) -> Result<(), String> {
+    let project = load_project(&project_id)?;
+
+    let mut pages = Vec::new();
+    for page_name in &project.page_order {
+        pages.push(load_page_content(&project_id, page_name)?);
     }
+
+    export_project_to_html(&project.title, pages, &output_path, project.cover_image.as_deref())
+}
+
+#[tauri::command]
+pub fn export_to_markdown(project_id: String, output_path: String) -> Result<(), String> {
+    file_service::export_project_to_markdown(&project_id, &output_path)
+}
+
+#[tauri::command]
+pub fn get_exports_dir() -> Result<String, String> {
+    let exports_dir = file_service::get_exports_dir()?;
     Ok(exports_dir.to_string_lossy().to_string())
 }
+
+#[tauri::command]
+pub fn cleanup_export_temp_files() -> Result<usize, String> {
+    cleanup_temp_exports()
+}
@@ -3,9 +3,11 @@ mod projects;
 mod ai;
 mod export;
 mod chat;
+mod backup;
 
 pub use config::*;
 pub use projects::*;
 pub use ai::*;
 pub use export::*;
 pub use chat::*;
+pub use backup::*;
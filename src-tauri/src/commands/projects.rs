@@ -1,24 +1,67 @@
-use crate::models::{ProjectMeta, ProjectListItem, Page};
+use crate::models::{ProjectMeta, ProjectListItem, TrashedProject, Page, PageDetails, PageVersion, ImportedPage, ProjectRevision, Annotation, ProjectStats};
 use crate::services::file_service;
 
+/// Placeholder content for pages created from the UI
+const NEW_PAGE_CONTENT: &str = "# New Page\n\nStart writing here...";
+
 #[tauri::command]
 pub fn list_projects() -> Result<Vec<ProjectListItem>, String> {
     file_service::list_all_projects()
 }
 
+#[tauri::command]
+pub fn list_projects_by_tag(tag: String) -> Result<Vec<ProjectListItem>, String> {
+    file_service::list_projects_by_tag(&tag)
+}
+
 #[tauri::command]
 pub fn get_project(id: String) -> Result<ProjectMeta, String> {
     file_service::load_project(&id)
 }
 
+#[tauri::command]
+pub fn get_project_stats(project_id: String) -> Result<ProjectStats, String> {
+    file_service::get_project_stats(&project_id)
+}
+
 #[tauri::command]
 pub fn create_project(title: String, description: String) -> Result<ProjectMeta, String> {
     file_service::create_new_project(&title, &description)
 }
 
+#[tauri::command]
+pub fn update_project(project_id: String, title: String, description: String) -> Result<ProjectMeta, String> {
+    file_service::update_project(&project_id, &title, &description)
+}
+
+#[tauri::command]
+pub fn set_project_tags(project_id: String, tags: Vec<String>) -> Result<ProjectMeta, String> {
+    file_service::set_project_tags(&project_id, tags)
+}
+
 #[tauri::command]
 pub fn delete_project(id: String) -> Result<(), String> {
-    file_service::delete_project_dir(&id)
+    file_service::trash_project(&id)
+}
+
+#[tauri::command]
+pub fn list_trashed_projects() -> Result<Vec<TrashedProject>, String> {
+    file_service::list_trashed_projects()
+}
+
+#[tauri::command]
+pub fn restore_project(id: String) -> Result<ProjectMeta, String> {
+    file_service::restore_project(&id)
+}
+
+#[tauri::command]
+pub fn empty_trash() -> Result<usize, String> {
+    file_service::empty_trash()
+}
+
+#[tauri::command]
+pub fn get_page(project_id: String, page_name: String) -> Result<PageDetails, String> {
+    file_service::load_page(&project_id, &page_name)
 }
 
 #[tauri::command]
@@ -33,21 +76,138 @@ pub fn save_page_content(project_id: String, page_name: String, content: String)
 
 #[tauri::command]
 pub fn add_page(project_id: String, title: String) -> Result<Page, String> {
-    let page_name = file_service::add_page_to_project(&project_id, &title, "# New Page\n\nStart writing here...")?;
+    let page_name = file_service::add_page_to_project(&project_id, &title, NEW_PAGE_CONTENT)?;
     Ok(Page {
         name: page_name.clone(),
         title,
     })
 }
 
+#[tauri::command]
+pub fn insert_page(project_id: String, title: String, index: usize) -> Result<Page, String> {
+    let page_name = file_service::insert_page_at(&project_id, &title, NEW_PAGE_CONTENT, index)?;
+    Ok(Page {
+        name: page_name,
+        title,
+    })
+}
+
+#[tauri::command]
+pub fn delete_page(project_id: String, page_name: String) -> Result<ProjectMeta, String> {
+    file_service::delete_page(&project_id, &page_name)
+}
+
+#[tauri::command]
+pub fn rename_page(project_id: String, old_name: String, new_title: String) -> Result<String, String> {
+    file_service::rename_page(&project_id, &old_name, &new_title)
+}
+
 #[tauri::command]
 pub fn reorder_pages(project_id: String, order: Vec<String>) -> Result<(), String> {
-    let mut meta = file_service::load_project(&project_id)?;
-    meta.page_order = order;
-    file_service::save_project(&meta)
+    file_service::reorder_pages(&project_id, order)?;
+    Ok(())
+}
+
+#[tauri::command]
+pub fn merge_pages(
+    project_id: String,
+    first_page: String,
+    second_page: String,
+    merged_title: String,
+) -> Result<ProjectMeta, String> {
+    file_service::merge_pages(&project_id, &first_page, &second_page, &merged_title)
+}
+
+#[tauri::command]
+pub fn split_page(project_id: String, page_name: String, split_heading: String) -> Result<ProjectMeta, String> {
+    file_service::split_page(&project_id, &page_name, &split_heading)
+}
+
+#[tauri::command]
+pub fn link_cross_references(project_id: String) -> Result<usize, String> {
+    file_service::link_cross_references(&project_id)
+}
+
+#[tauri::command]
+pub fn list_page_versions(project_id: String, page_name: String) -> Result<Vec<PageVersion>, String> {
+    file_service::list_page_versions(&project_id, &page_name)
+}
+
+#[tauri::command]
+pub fn restore_page_version(project_id: String, page_name: String, timestamp: String) -> Result<String, String> {
+    file_service::restore_page_version(&project_id, &page_name, &timestamp)
+}
+
+#[tauri::command]
+pub fn list_annotations(project_id: String, page_name: Option<String>) -> Result<Vec<Annotation>, String> {
+    file_service::list_annotations(&project_id, page_name.as_deref())
+}
+
+#[tauri::command]
+pub fn add_annotation(project_id: String, page_name: String, line: usize, text: String) -> Result<Annotation, String> {
+    file_service::add_annotation(&project_id, &page_name, line, &text)
+}
+
+#[tauri::command]
+pub fn delete_annotation(project_id: String, annotation_id: String) -> Result<(), String> {
+    file_service::delete_annotation(&project_id, &annotation_id)
+}
+
+#[tauri::command]
+pub fn import_folder(
+    folder_path: String,
+    title: String,
+    description: String,
+    recursive: Option<bool>,
+) -> Result<ProjectMeta, String> {
+    file_service::import_folder_as_project(&folder_path, &title, &description, recursive.unwrap_or(false))
+}
+
+#[tauri::command]
+pub fn append_folder_to_project(
+    project_id: String,
+    folder_path: String,
+    recursive: Option<bool>,
+) -> Result<ProjectMeta, String> {
+    file_service::append_folder_to_project(&project_id, &folder_path, recursive.unwrap_or(false))
+}
+
+#[tauri::command]
+pub fn import_markdown_file(project_id: Option<String>, file_path: String) -> Result<ImportedPage, String> {
+    file_service::import_markdown_file(project_id.as_deref(), &file_path)
+}
+
+#[tauri::command]
+pub fn duplicate_project(project_id: String) -> Result<ProjectMeta, String> {
+    file_service::duplicate_project(&project_id)
+}
+
+#[tauri::command]
+pub fn set_project_model(project_id: String, model: Option<String>) -> Result<ProjectMeta, String> {
+    file_service::set_project_model(&project_id, model)
+}
+
+#[tauri::command]
+pub fn set_project_cover(project_id: String, cover: Option<String>) -> Result<ProjectMeta, String> {
+    file_service::set_project_cover(&project_id, cover)
+}
+
+#[tauri::command]
+pub fn set_custom_instructions(project_id: String, instructions: Option<String>) -> Result<ProjectMeta, String> {
+    file_service::set_custom_instructions(&project_id, instructions)
+}
+
+#[tauri::command]
+pub fn snapshot_project(project_id: String, name: String) -> Result<ProjectRevision, String> {
+    file_service::snapshot_project(&project_id, &name)
+}
+
+#[tauri::command]
+pub fn list_project_revisions(project_id: String) -> Result<Vec<ProjectRevision>, String> {
+    file_service::list_project_revisions(&project_id)
 }
 
 #[tauri::command]
-pub fn import_folder(folder_path: String, title: String, description: String) -> Result<ProjectMeta, String> {
-    file_service::import_folder_as_project(&folder_path, &title, &description)
+pub fn restore_project_revision(project_id: String, name: String) -> Result<ProjectMeta, String> {
+    file_service::restore_project_revision(&project_id, &name)
 }
@@ -3,6 +3,7 @@ mod models;
 mod services;
 
 use commands::*;
+use services::file_service;
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
@@ -16,37 +17,119 @@ pub fn run() {
             set_api_key,
             get_base_url,
             set_base_url,
+            test_connection,
             get_model,
             set_model,
             get_provider,
             set_provider,
+            provider_presets,
+            apply_provider_preset,
+            set_chat_autosave,
+            set_extra_params,
+            get_context_limit,
+            set_context_limit,
+            set_web_search,
+            set_fallback_model,
+            set_max_iterations,
+            set_sampling_defaults,
+            set_temperature,
+            set_generation_seed,
+            set_request_timeout,
+            set_proxy,
             get_config,
+            get_storage_paths,
+            get_data_dir,
+            open_data_dir,
             // Project commands
             list_projects,
+            list_projects_by_tag,
             get_project,
+            get_project_stats,
             create_project,
+            update_project,
+            set_project_tags,
             delete_project,
+            list_trashed_projects,
+            restore_project,
+            empty_trash,
+            duplicate_project,
+            set_project_model,
+            set_project_cover,
+            set_custom_instructions,
+            snapshot_project,
+            list_project_revisions,
+            restore_project_revision,
             // Page commands
+            get_page,
             get_page_content,
             save_page_content,
             add_page,
+            insert_page,
+            delete_page,
+            rename_page,
             reorder_pages,
+            merge_pages,
+            split_page,
+            link_cross_references,
+            list_page_versions,
+            restore_page_version,
+            list_annotations,
+            add_annotation,
+            delete_annotation,
             import_folder,
+            append_folder_to_project,
+            import_markdown_file,
             // AI commands
             generate_learning,
+            generate_from_objectives,
+            resume_generation,
+            cancel_generation,
+            regenerate_page,
+            continue_page,
+            generate_quiz,
+            generate_glossary,
+            verify_objectives_covered,
+            assess_completeness,
+            generate_series,
             expand_selection,
+            preview_expansion,
+            accept_expansion,
+            reject_expansion,
+            estimate_expansion_context,
             remove_expansion,
+            undo_last_expansion,
+            list_expansions,
             answer_question,
             // Chat commands
             list_chat_sessions,
             create_chat_session,
             get_chat_session,
+            get_session_actions,
+            revert_chat_changes,
+            close_chat_session,
             delete_chat_session,
             send_chat_message,
+            cancel_chat,
             // Export commands
             export_to_pdf,
+            export_to_html,
+            export_to_markdown,
             get_exports_dir,
+            cleanup_export_temp_files,
+            // Backup commands
+            create_backup,
+            restore_backup,
+            export_project_bundle,
+            import_project_bundle,
         ])
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(tauri::generate_context!())
+        .expect("error while building tauri application")
+        .run(|_app, event| {
+            // Make sure batched chat messages hit the disk before we go away
+            if let tauri::RunEvent::Exit = event {
+                if let Err(e) = file_service::flush_all_chat_sessions() {
+                    eprintln!("Failed to flush chat sessions on exit: {}", e);
+                }
+            }
+        });
 }
@@ -0,0 +1,15 @@
+use serde::{Deserialize, Serialize};
+use chrono::{DateTime, Utc};
+
+/// A private margin note on a page, stored apart from the page content in
+/// the project's `annotations.json`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Annotation {
+    pub id: String,
+    pub page_name: String,
+    /// Line (1-indexed) the note is attached to
+    pub line: usize,
+    pub text: String,
+    pub created_at: DateTime<Utc>,
+}
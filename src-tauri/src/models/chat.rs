@@ -7,6 +7,9 @@ pub struct ChatMessage {
     pub role: String,        // "user" or "assistant"
     pub content: String,
     pub timestamp: DateTime<Utc>,
+    /// Set on assistant replies cut short by the user stopping the agent
+    #[serde(default)]
+    pub interrupted: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -16,10 +19,42 @@ pub struct ChatSession {
     pub project_id: String,
     pub title: String,
     pub messages: Vec<ChatMessage>,
+    /// Tools the editing agent ran in this session, oldest first
+    #[serde(default)]
+    pub tool_actions: Vec<ToolAction>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
 
+/// A single tool call made by the editing agent, kept as an audit log
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ToolAction {
+    pub tool_name: String,
+    pub arguments: String,
+    pub success: bool,
+    pub timestamp: DateTime<Utc>,
+    /// Page the action touched (the new filename for create_file)
+    #[serde(default)]
+    pub page_name: Option<String>,
+    /// Page content before an edit_file or delete_file, for reverting
+    #[serde(default)]
+    pub previous_content: Option<String>,
+    /// Page position before a move_file or delete_file, for reverting
+    #[serde(default)]
+    pub previous_index: Option<usize>,
+    #[serde(default)]
+    pub reverted: bool,
+}
+
+/// Outcome of undoing a chat session's tool actions
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChatRevertSummary {
+    pub reverted: Vec<String>,
+    pub skipped: Vec<String>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ChatSessionListItem {
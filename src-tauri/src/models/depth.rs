@@ -0,0 +1,53 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+/// How deep a generated book should go
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Depth {
+    Beginner,
+    Intermediate,
+    Advanced,
+}
+
+impl Depth {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Depth::Beginner => "beginner",
+            Depth::Intermediate => "intermediate",
+            Depth::Advanced => "advanced",
+        }
+    }
+
+    /// What this depth means for the content, spelled out for the model
+    pub fn guidance(&self) -> &'static str {
+        match self {
+            Depth::Beginner => "Assume no prior knowledge. Keep it brief: a handful of short chapters that explain the core ideas in plain language, define every term, and favour intuition and simple examples over formalism.",
+            Depth::Intermediate => "Assume the reader knows the basics of the field. Cover the topic in a standard-length book with worked examples, common pitfalls and how the pieces fit together.",
+            Depth::Advanced => "Assume a strong background. Be comprehensive: many chapters covering edge cases, formal definitions, derivations, trade-offs and current practice in depth.",
+        }
+    }
+}
+
+impl fmt::Display for Depth {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for Depth {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "beginner" => Ok(Depth::Beginner),
+            "intermediate" => Ok(Depth::Intermediate),
+            "advanced" => Ok(Depth::Advanced),
+            other => Err(format!(
+                "Unknown depth '{}': expected beginner, intermediate or advanced",
+                other
+            )),
+        }
+    }
+}
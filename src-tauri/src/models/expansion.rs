@@ -1,4 +1,5 @@
 use serde::{Deserialize, Serialize};
+use chrono::{DateTime, Utc};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -18,4 +19,42 @@ pub struct ExpansionResult {
     pub inserted_content: String,
     pub insertion_line: usize,
     pub updated_lines: Vec<usize>,
+    /// Pages created by the expansion, filled in once it is saved
+    #[serde(default)]
+    pub added_pages: Vec<String>,
+    /// Pages removed by the expansion, filled in once it is saved
+    #[serde(default)]
+    pub deleted_pages: Vec<String>,
+    /// Set when the patch couldn't be matched to the page and was placed
+    /// after the selection instead
+    #[serde(default)]
+    pub approximate: bool,
+}
+
+/// A saved expansion, kept in the project's `expansions.json` so it can be
+/// listed and reverted
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExpansionRecord {
+    pub expansion_id: String,
+    pub page_name: String,
+    /// First line (1-indexed) of the changed region in the page
+    pub start_line: usize,
+    /// Lines the expansion put into the page, starting at `start_line`
+    pub inserted_content: String,
+    /// Lines the expansion replaced, put back when it is removed
+    #[serde(default)]
+    pub replaced_content: String,
+    /// The whole page as it was before the expansion, for undo
+    #[serde(default)]
+    pub previous_content: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ContextEstimate {
+    pub estimated_tokens: usize,
+    pub context_limit: usize,
+    pub fits: bool,
 }
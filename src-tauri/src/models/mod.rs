@@ -2,8 +2,18 @@ mod project;
 mod page;
 mod expansion;
 mod chat;
+mod revision;
+mod quiz;
+mod glossary;
+mod depth;
+mod annotation;
 
 pub use project::*;
 pub use page::*;
 pub use expansion::*;
 pub use chat::*;
+pub use revision::*;
+pub use quiz::*;
+pub use glossary::*;
+pub use depth::*;
+pub use annotation::*;
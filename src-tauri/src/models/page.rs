@@ -1,4 +1,5 @@
 use serde::{Deserialize, Serialize};
+use chrono::{DateTime, Utc};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -6,3 +7,32 @@ pub struct Page {
     pub name: String,
     pub title: String,
 }
+
+/// A page with its content and details derived from it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PageDetails {
+    pub name: String,
+    /// First `# ` heading, or the filename when there is none
+    pub title: String,
+    pub content: String,
+    pub word_count: usize,
+}
+
+/// A previous version of a page kept in its history
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PageVersion {
+    /// Identifies the version for `restore_page_version`
+    pub timestamp: String,
+    pub saved_at: DateTime<Utc>,
+    pub size: u64,
+}
+
+/// Where a single imported markdown file ended up
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportedPage {
+    pub project_id: String,
+    pub page_name: String,
+}
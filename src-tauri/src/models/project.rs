@@ -12,6 +12,78 @@ pub struct ProjectMeta {
     pub updated_at: DateTime<Utc>,
     #[serde(default)]
     pub page_order: Vec<String>,
+    /// Learning objectives the book was generated to cover
+    #[serde(default)]
+    pub objectives: Vec<String>,
+    /// Sources cited by web search during generation
+    #[serde(default)]
+    pub references: Vec<Citation>,
+    /// Tokens consumed generating this project, when the provider reports usage
+    #[serde(default)]
+    pub total_tokens: u64,
+    /// Language the content is written in (English when unset)
+    #[serde(default)]
+    pub language: Option<String>,
+    /// Extra style guidance appended to the AI system prompts
+    #[serde(default)]
+    pub custom_instructions: Option<String>,
+    /// User labels for organizing the library
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Model used for this project instead of the configured one
+    #[serde(default)]
+    pub model_override: Option<String>,
+    /// Cover image for exports, as a file path or data URI
+    #[serde(default)]
+    pub cover_image: Option<String>,
+    /// Rough length each chapter should be written to, in words
+    #[serde(default)]
+    pub target_words_per_chapter: Option<usize>,
+}
+
+/// Chapter plan produced before writing in outline-first generation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BookOutline {
+    pub title: String,
+    #[serde(default)]
+    pub description: String,
+    pub chapters: Vec<OutlineChapter>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OutlineChapter {
+    pub title: String,
+    #[serde(default)]
+    pub summary: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CompletenessAssessment {
+    pub score: u32,
+    #[serde(default)]
+    pub summary: String,
+    #[serde(default)]
+    pub missing_topics: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Citation {
+    pub url: String,
+    #[serde(default)]
+    pub title: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ObjectiveCoverage {
+    pub objective: String,
+    pub covered: bool,
+    #[serde(default)]
+    pub evidence: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -22,6 +94,8 @@ pub struct ProjectListItem {
     pub description: String,
     pub page_count: usize,
     pub updated_at: DateTime<Utc>,
+    pub total_tokens: u64,
+    pub tags: Vec<String>,
 }
 
 impl From<ProjectMeta> for ProjectListItem {
@@ -32,6 +106,32 @@ impl From<ProjectMeta> for ProjectListItem {
             description: meta.description,
             page_count: meta.page_order.len(),
             updated_at: meta.updated_at,
+            total_tokens: meta.total_tokens,
+            tags: meta.tags,
         }
     }
 }
+
+/// Totals computed from a project's pages, for a project info panel
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectStats {
+    pub page_count: usize,
+    pub total_words: usize,
+    pub total_characters: usize,
+    /// Fenced (```) code blocks across all pages
+    pub code_block_count: usize,
+    pub reading_time_minutes: usize,
+    pub chat_session_count: usize,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// A deleted project waiting in the trash
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TrashedProject {
+    #[serde(flatten)]
+    pub project: ProjectListItem,
+    pub deleted_at: DateTime<Utc>,
+}
@@ -0,0 +1,17 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Quiz {
+    pub questions: Vec<QuizQuestion>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QuizQuestion {
+    pub question: String,
+    pub choices: Vec<String>,
+    pub answer_index: usize,
+    #[serde(default)]
+    pub explanation: String,
+}
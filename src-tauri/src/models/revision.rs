@@ -0,0 +1,33 @@
+use serde::{Deserialize, Serialize};
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+use super::ProjectMeta;
+
+/// Stored manifest for a named whole-project revision
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RevisionManifest {
+    pub name: String,
+    pub created_at: DateTime<Utc>,
+    pub meta: ProjectMeta,
+    /// Page filename -> content hash of the stored blob
+    pub pages: HashMap<String, String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectRevision {
+    pub name: String,
+    pub created_at: DateTime<Utc>,
+    pub page_count: usize,
+}
+
+impl From<&RevisionManifest> for ProjectRevision {
+    fn from(manifest: &RevisionManifest) -> Self {
+        Self {
+            name: manifest.name.clone(),
+            created_at: manifest.created_at,
+            page_count: manifest.meta.page_order.len(),
+        }
+    }
+}
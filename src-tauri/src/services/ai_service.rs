@@ -1,10 +1,19 @@
-use crate::models::{ProjectMeta, SelectionRange, ExpansionResult, ChatMessage};
+use crate::models::{
+    ProjectMeta, SelectionRange, ExpansionResult, ChatMessage, ContextEstimate, ObjectiveCoverage,
+    Citation, CompletenessAssessment, BookOutline, Quiz, Depth, ToolAction, ChatRevertSummary, GlossaryEntry
+};
 use crate::services::file_service::{
     create_new_project, add_page_to_project, load_page_content,
-    save_page_content, load_project, load_chat_session, save_chat_session
+    save_page_content, load_project, load_chat_session, save_chat_session,
+    queue_chat_session_save, flush_chat_session, extract_page_title
 };
 use crate::services::llm_client::LlmClient;
+use crate::services::config_service;
 
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, Instant};
 use uuid::Uuid;
 use regex::Regex;
 use serde::{Deserialize, Serialize};
@@ -38,9 +47,13 @@ pub struct AgentState {
     pub is_finished: bool,
     pub iteration: u32,
     pub max_iterations: u32,
+    /// Tokens consumed so far, as reported by the provider
+    pub total_tokens: u64,
+    /// Chapter plan, in outline-first mode
+    pub outline: Option<BookOutline>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PageInfo {
     pub filename: String,
     pub title: String,
@@ -52,6 +65,21 @@ pub struct AgentStatusEvent {
     pub message: String,
     pub iteration: u32,
     pub tool_name: Option<String>,
+    /// Tokens used by the whole run; only set on the final event
+    pub total_tokens: Option<u64>,
+    /// Chapters written so far, for generation runs
+    #[serde(default)]
+    pub chapters_done: Option<usize>,
+    /// Planned chapter count, when known up front (outline-first mode)
+    #[serde(default)]
+    pub chapters_total: Option<usize>,
+}
+
+/// Event payload for partial agent output while a response streams in
+#[derive(Debug, Clone, Serialize)]
+pub struct AgentTokenEvent {
+    pub token: String,
+    pub iteration: u32,
 }
 
 // ============================================================================
@@ -174,10 +202,115 @@ Your reasoning about what to do next...
 
 IMPORTANT: Always respond with exactly one tool call. Never output raw content without a tool call wrapper."##;
 
+// ============================================================================
+// CONTEXT TRIMMING
+// ============================================================================
+
+/// Latest messages the trimmer never touches, so the agent keeps its
+/// current exchanges verbatim
+const TRIM_KEEP_RECENT_MESSAGES: usize = 6;
+
+/// Tokens kept free in the context window for an agent's reply
+const AGENT_RESPONSE_RESERVE_TOKENS: usize = GENERATION_MAX_TOKENS as usize;
+
+/// Token budget for an agent's message history: the configured context
+/// window less room for the reply
+fn agent_context_budget() -> usize {
+    config_service::get_context_limit()
+        .unwrap_or(config_service::DEFAULT_CONTEXT_LIMIT_TOKENS)
+        .saturating_sub(AGENT_RESPONSE_RESERVE_TOKENS)
+}
+
+fn estimate_message_tokens(messages: &[crate::services::llm_client::ChatMessage]) -> usize {
+    // A few tokens of per-message framing on top of the content
+    messages.iter().map(|m| estimate_tokens(&m.content) + 4).sum()
+}
+
+/// Shrink an agent's history to fit `budget` tokens. The leading system
+/// prompt(s), the first user message (the task) and the latest
+/// TRIM_KEEP_RECENT_MESSAGES are kept as-is. Older tool results and tool
+/// calls are first cut to one-line summaries, oldest first; if that isn't
+/// enough the oldest messages are dropped.
+fn trim_agent_messages(messages: &mut Vec<crate::services::llm_client::ChatMessage>, budget: usize) {
+    let mut total = estimate_message_tokens(messages);
+    if total <= budget {
+        return;
+    }
+
+    let system_count = messages.iter().take_while(|m| m.role == "system").count();
+    let first = (system_count + 1).min(messages.len());
+    let last = messages.len().saturating_sub(TRIM_KEEP_RECENT_MESSAGES).max(first);
+
+    for message in &mut messages[first..last] {
+        if let Some(summary) = summarize_agent_message(&message.content) {
+            total = total - estimate_tokens(&message.content) + estimate_tokens(&summary);
+            message.content = summary;
+            if total <= budget {
+                return;
+            }
+        }
+    }
+
+    // Still over: drop the oldest of those messages
+    let mut end = first;
+    while end < last && total > budget {
+        total -= estimate_tokens(&messages[end].content) + 4;
+        end += 1;
+    }
+    messages.drain(first..end);
+}
+
+/// One-line stand-in for an old tool result or tool call, or None when the
+/// message is neither (or already short)
+fn summarize_agent_message(content: &str) -> Option<String> {
+    const SUMMARY_MIN_CHARS: usize = 300;
+    if content.chars().count() < SUMMARY_MIN_CHARS {
+        return None;
+    }
+
+    // Tool results: "Tool 'x' executed successfully:\n<output>"
+    if content.starts_with("Tool '") {
+        let (status, output) = content.split_once('\n').unwrap_or((content, ""));
+        let first_line = output.lines().find(|l| !l.trim().is_empty()).unwrap_or("");
+        return Some(format!("{}\n{} [older output trimmed]", status, truncate_text(first_line, 120)));
+    }
+
+    // Tool calls: note the tool and its short arguments. Written as prose
+    // so the model doesn't copy a trimmed call as a real one.
+    let tool_call = parse_tool_call(content).ok()?;
+    Some(format!(
+        "[Earlier call to {}({}), trimmed]",
+        tool_call.name,
+        summarize_tool_arguments(&tool_call.arguments)
+    ))
+}
+
 // ============================================================================
 // TOOL EXECUTION
 // ============================================================================
 
+/// Closing tag of a tool call. The agent loops stop generation on it so a
+/// model can't run on into a second tool call.
+const TOOL_CALL_END: &str = "</tool_call>";
+
+fn tool_call_stop() -> Option<Vec<String>> {
+    Some(vec![TOOL_CALL_END.to_string()])
+}
+
+/// Restore the closing tag a stop sequence cut off, so the response parses
+/// and reads as a complete tool call in the conversation history. Models
+/// that don't accept stop sequences get anything after the first tool call
+/// dropped here instead.
+fn close_tool_call(response: String) -> String {
+    if !response.contains("<tool_call>") {
+        return response;
+    }
+    match response.find(TOOL_CALL_END) {
+        Some(end) => response[..end + TOOL_CALL_END.len()].to_string(),
+        None => format!("{}\n{}", response.trim_end(), TOOL_CALL_END),
+    }
+}
+
 /// Parse tool call from agent response
 fn parse_tool_call(response: &str) -> Result<ToolCall, String> {
     // Try to find JSON in <tool_call> tags first
@@ -189,9 +322,8 @@ fn parse_tool_call(response: &str) -> Result<ToolCall, String> {
     }
 
     // Fallback: find any JSON object with "tool" key
-    let json_re = Regex::new(r#"\{[^{}]*"tool"[^{}]*\}"#).unwrap();
-    if let Some(mat) = json_re.find(response) {
-        return parse_tool_json(mat.as_str());
+    if let Some(json_str) = find_tool_json(response) {
+        return parse_tool_json(json_str);
     }
 
     // Try to find JSON in code blocks
@@ -204,6 +336,61 @@ fn parse_tool_call(response: &str) -> Result<ToolCall, String> {
     Err(format!("No valid tool call found in response: {}", &response[..response.len().min(200)]))
 }
 
+/// Find the first balanced JSON object in free text that has a "tool" key.
+/// Nested objects in the arguments and braces inside strings are handled
+/// by matching braces rather than with a regex.
+fn find_tool_json(response: &str) -> Option<&str> {
+    for (start, _) in response.match_indices('{') {
+        let Some(len) = balanced_object_len(&response[start..]) else {
+            continue;
+        };
+        let candidate = &response[start..start + len];
+        if !candidate.contains("\"tool\"") {
+            continue;
+        }
+        let is_tool_call = serde_json::from_str::<serde_json::Value>(candidate)
+            .map(|v| v.get("tool").and_then(|t| t.as_str()).is_some())
+            .unwrap_or(false);
+        if is_tool_call {
+            return Some(candidate);
+        }
+    }
+    None
+}
+
+/// Byte length of the object starting at the `{` that opens `text`, up to
+/// and including its matching `}`, skipping over string literals
+fn balanced_object_len(text: &str) -> Option<usize> {
+    let mut depth = 0usize;
+    let mut in_string = false;
+    let mut escaped = false;
+
+    for (i, c) in text.char_indices() {
+        if in_string {
+            if escaped {
+                escaped = false;
+            } else if c == '\\' {
+                escaped = true;
+            } else if c == '"' {
+                in_string = false;
+            }
+            continue;
+        }
+        match c {
+            '"' => in_string = true,
+            '{' => depth += 1,
+            '}' => {
+                depth = depth.checked_sub(1)?;
+                if depth == 0 {
+                    return Some(i + 1);
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
 fn parse_tool_json(json_str: &str) -> Result<ToolCall, String> {
     // Parse the JSON
     let parsed: serde_json::Value = serde_json::from_str(json_str)
@@ -271,6 +458,11 @@ fn execute_create_file(tool_call: &ToolCall, state: &mut AgentState) -> ToolResu
 }
 
 fn execute_edit_file(tool_call: &ToolCall, state: &mut AgentState) -> ToolResult {
+    edit_page_file(&state.project_id, tool_call)
+}
+
+/// Replace `old_content` with `new_content` in a page (shared by both agents)
+fn edit_page_file(project_id: &str, tool_call: &ToolCall) -> ToolResult {
     let filename = tool_call.arguments.get("filename")
         .and_then(|v| v.as_str())
         .unwrap_or("");
@@ -284,7 +476,7 @@ fn execute_edit_file(tool_call: &ToolCall, state: &mut AgentState) -> ToolResult
         .unwrap_or("");
 
     // Load existing content
-    let current_content = match load_page_content(&state.project_id, filename) {
+    let current_content = match load_page_content(project_id, filename) {
         Ok(c) => c,
         Err(e) => return ToolResult {
             tool_name: "edit_file".to_string(),
@@ -302,9 +494,31 @@ fn execute_edit_file(tool_call: &ToolCall, state: &mut AgentState) -> ToolResult
         };
     }
 
+    // Refuse to guess which occurrence the agent meant
+    let occurrences = current_content.matches(old_content).count();
+    if occurrences > 1 {
+        return ToolResult {
+            tool_name: "edit_file".to_string(),
+            success: false,
+            output: format!(
+                "old_content matches {} places in '{}'. Include more surrounding text so it matches exactly one.",
+                occurrences, filename
+            ),
+        };
+    }
+
     let updated_content = current_content.replacen(old_content, new_content, 1);
 
-    match save_page_content(&state.project_id, filename, &updated_content) {
+    // Don't let the agent believe a no-op replacement made progress
+    if updated_content == current_content {
+        return ToolResult {
+            tool_name: "edit_file".to_string(),
+            success: false,
+            output: format!("No effective change: the edit to '{}' left the file identical. new_content must differ from old_content.", filename),
+        };
+    }
+
+    match save_page_content(project_id, filename, &updated_content) {
         Ok(()) => ToolResult {
             tool_name: "edit_file".to_string(),
             success: true,
@@ -467,21 +681,31 @@ fn extract_agent_thinking(response: &str) -> Option<String> {
     None
 }
 
-/// Truncate text to max length, adding ellipsis if needed
+/// Status line for a reasoning model's separate chain-of-thought: its most
+/// recent non-empty line
+fn summarize_reasoning(reasoning: &str) -> Option<String> {
+    reasoning.lines()
+        .rev()
+        .map(|line| truncate_text(line, 80))
+        .find(|line| !line.is_empty())
+}
+
+/// Truncate text to `max_len` characters, adding ellipsis if needed. Cuts on
+/// a char boundary, so CJK and other multi-byte text is safe.
 fn truncate_text(text: &str, max_len: usize) -> String {
     let trimmed = text.trim();
-    if trimmed.len() <= max_len {
-        trimmed.to_string()
-    } else {
-        // Try to break at a word boundary
-        let truncated = &trimmed[..max_len];
-        if let Some(last_space) = truncated.rfind(' ') {
-            if last_space > max_len / 2 {
-                return format!("{}...", &trimmed[..last_space]);
-            }
+    let Some((cut, _)) = trimmed.char_indices().nth(max_len) else {
+        return trimmed.to_string();
+    };
+
+    // Try to break at a word boundary
+    let truncated = &trimmed[..cut];
+    if let Some(last_space) = truncated.rfind(' ') {
+        if last_space > cut / 2 {
+            return format!("{}...", &trimmed[..last_space]);
         }
-        format!("{}...", truncated)
     }
+    format!("{}...", truncated)
 }
 
 /// Emit agent status event to frontend
@@ -490,58 +714,550 @@ fn emit_agent_status(app: &AppHandle, message: &str, iteration: u32, tool_name:
         message: message.to_string(),
         iteration,
         tool_name: tool_name.map(|s| s.to_string()),
+        total_tokens: None,
+        chapters_done: None,
+        chapters_total: None,
     };
     let _ = app.emit("agent-status", event);
 }
 
-/// Generate learning material using an iterative agent loop
+/// Emit agent status for a generation run, with chapter progress
+fn emit_generation_status(app: &AppHandle, message: &str, state: &AgentState, tool_name: Option<&str>) {
+    let event = AgentStatusEvent {
+        message: message.to_string(),
+        iteration: state.iteration,
+        tool_name: tool_name.map(|s| s.to_string()),
+        total_tokens: None,
+        chapters_done: Some(state.pages.len()),
+        chapters_total: state.outline.as_ref().map(|o| o.chapters.len()),
+    };
+    let _ = app.emit("agent-status", event);
+}
+
+/// Readable name for common language codes; anything else is passed through
+fn language_name(language: &str) -> &str {
+    match language.to_lowercase().as_str() {
+        "en" => "English",
+        "es" => "Spanish",
+        "fr" => "French",
+        "de" => "German",
+        "it" => "Italian",
+        "pt" => "Portuguese",
+        "nl" => "Dutch",
+        "ru" => "Russian",
+        "ja" => "Japanese",
+        "ko" => "Korean",
+        "zh" => "Chinese",
+        "ar" => "Arabic",
+        "hi" => "Hindi",
+        _ => language,
+    }
+}
+
+/// A base system prompt plus the project's own guidance (content language,
+/// custom instructions), so generation and later edits stay consistent
+fn project_system_prompt(base: &str, project: &ProjectMeta) -> String {
+    let mut prompt = base.to_string();
+    if let Some(language) = &project.language {
+        prompt.push_str(&format!(
+            "\n\n## Language\nWrite all content in {}.",
+            language_name(language)
+        ));
+    }
+    if let Some(target) = project.target_words_per_chapter {
+        prompt.push_str(&format!(
+            "\n\n## Chapter Length\nEach chapter should be roughly {} words.",
+            target
+        ));
+    }
+    if let Some(instructions) = &project.custom_instructions {
+        prompt.push_str(&format!("\n\n## Author Instructions\n{}", instructions));
+    }
+    prompt
+}
+
+/// Create the project a generation run writes into. The title is a
+/// placeholder until the agent names the book.
+fn create_generation_project(
+    topic: &str,
+    language: Option<&str>,
+    target_words_per_chapter: Option<usize>,
+) -> Result<ProjectMeta, String> {
+    let mut project = create_new_project(topic, "")?;
+    if language.is_some() || target_words_per_chapter.is_some() {
+        project.language = language.map(|l| l.to_string());
+        project.target_words_per_chapter = target_words_per_chapter;
+        crate::services::file_service::save_project(&project)?;
+    }
+    Ok(project)
+}
+
+/// Word count of a chapter that came back under half its length target
+fn short_chapter_words(content: &str, target: Option<usize>) -> Option<usize> {
+    let target = target?;
+    let words = content.split_whitespace().count();
+    (words * 2 < target).then_some(words)
+}
+
+/// Generate learning material using an iterative agent loop. Content is
+/// written in `language` (English when None), with chapters aimed at
+/// `target_words_per_chapter` words when given.
 pub async fn generate_learning_material(
     topic: &str,
-    depth: &str,
+    depth: Depth,
+    language: Option<&str>,
+    target_words_per_chapter: Option<usize>,
     _api_key: &str,
     app: AppHandle,
 ) -> Result<ProjectMeta, String> {
     // Create LLM client from config
-    let client = LlmClient::from_config()?;
+    let client = LlmClient::from_config(None)?;
 
     // Emit initial status
     emit_agent_status(&app, "Starting content generation...", 0, None);
 
     // Create the project first (title and description will be set by the agent)
-    let project = create_new_project(topic, "")?;
+    let project = create_generation_project(topic, language, target_words_per_chapter)?;
+
+    // Build initial user message
+    let mut initial_prompt = format!(
+        "Create comprehensive learning material about: {}\n\nDepth level: {}\n{}\n\nStart by creating the first chapter (introduction/overview). Then continue creating chapters until you have covered the topic thoroughly at the specified depth level. Call the finish tool when done.",
+        topic, depth, depth.guidance()
+    );
+    if let Some(language) = language {
+        initial_prompt.push_str(&format!("\n\nWrite all content in {}.", language_name(language)));
+    }
+
+    run_generation_agent(client, &project.id, &initial_prompt, &app).await
+}
+
+/// Generate learning material structured around a list of learning objectives
+pub async fn generate_from_objectives(
+    topic: &str,
+    objectives: &[String],
+    depth: Depth,
+    _api_key: &str,
+    app: AppHandle,
+) -> Result<ProjectMeta, String> {
+    // Create LLM client from config
+    let client = LlmClient::from_config(None)?;
+
+    // Emit initial status
+    emit_agent_status(&app, "Starting content generation...", 0, None);
+
+    // Create the project and remember its objectives for later verification
+    let mut project = create_new_project(topic, "")?;
+    project.objectives = objectives.to_vec();
+    crate::services::file_service::save_project(&project)?;
+
+    let objective_list: Vec<String> = objectives.iter()
+        .enumerate()
+        .map(|(i, o)| format!("{}. {}", i + 1, o))
+        .collect();
+
+    // Build initial user message
+    let initial_prompt = format!(
+        "Create comprehensive learning material about: {}\n\nThe reader must be able to achieve these learning objectives:\n{}\n\nDepth level: {}\n{}\n\nCover every objective in its own chapter or a clearly identified section, and make it explicit which objective each chapter addresses (for example, end each chapter with a short \"Objectives covered\" note listing them by number). Start by creating the first chapter (introduction/overview). Call the finish tool once every objective has been covered.",
+        topic, objective_list.join("\n"), depth, depth.guidance()
+    );
+
+    run_generation_agent(client, &project.id, &initial_prompt, &app).await
+}
+
+/// Response length cap for the generation agent when none is configured
+const GENERATION_MAX_TOKENS: u32 = 16_384;
+
+/// Cancellation flags for in-flight generation runs, keyed by project id
+fn generation_cancel_flags() -> &'static Mutex<HashMap<String, Arc<AtomicBool>>> {
+    static FLAGS: OnceLock<Mutex<HashMap<String, Arc<AtomicBool>>>> = OnceLock::new();
+    FLAGS.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/// Cancellation flags for in-flight chat agent runs, keyed by session id
+fn chat_cancel_flags() -> &'static Mutex<HashMap<String, Arc<AtomicBool>>> {
+    static FLAGS: OnceLock<Mutex<HashMap<String, Arc<AtomicBool>>>> = OnceLock::new();
+    FLAGS.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/// Keeps a cancel flag registered for the lifetime of an agent run
+struct CancelRegistration {
+    registry: &'static Mutex<HashMap<String, Arc<AtomicBool>>>,
+    key: String,
+    flag: Arc<AtomicBool>,
+}
+
+impl CancelRegistration {
+    fn new(registry: &'static Mutex<HashMap<String, Arc<AtomicBool>>>, key: &str) -> Self {
+        let flag = Arc::new(AtomicBool::new(false));
+        if let Ok(mut flags) = registry.lock() {
+            flags.insert(key.to_string(), flag.clone());
+        }
+        Self { registry, key: key.to_string(), flag }
+    }
+
+    fn is_cancelled(&self) -> bool {
+        self.flag.load(Ordering::SeqCst)
+    }
+}
+
+impl Drop for CancelRegistration {
+    fn drop(&mut self) {
+        if let Ok(mut flags) = self.registry.lock() {
+            // Only remove our own flag, not one from a newer run
+            if flags.get(&self.key).is_some_and(|f| Arc::ptr_eq(f, &self.flag)) {
+                flags.remove(&self.key);
+            }
+        }
+    }
+}
+
+/// Writes any batched chat messages to disk when an agent run ends, so an
+/// early error return doesn't leave them stranded in the save queue
+struct ChatSessionFlush {
+    project_id: String,
+    session_id: String,
+}
+
+impl ChatSessionFlush {
+    fn new(project_id: &str, session_id: &str) -> Self {
+        Self { project_id: project_id.to_string(), session_id: session_id.to_string() }
+    }
+}
+
+impl Drop for ChatSessionFlush {
+    fn drop(&mut self) {
+        if let Err(e) = flush_chat_session(&self.project_id, &self.session_id) {
+            eprintln!("Failed to flush chat session {}: {}", self.session_id, e);
+        }
+    }
+}
+
+/// Ask the agent generating a project to stop after its current step
+pub fn cancel_generation(project_id: &str) -> Result<(), String> {
+    let flags = generation_cancel_flags()
+        .lock()
+        .map_err(|e| format!("Failed to lock generation state: {}", e))?;
+    let flag = flags
+        .get(project_id)
+        .ok_or_else(|| "No generation is running for this project".to_string())?;
+    flag.store(true, Ordering::SeqCst);
+    Ok(())
+}
+
+/// Ask the chat agent working in a session to stop before its next step
+pub fn cancel_chat(session_id: &str) -> Result<(), String> {
+    let flags = chat_cancel_flags()
+        .lock()
+        .map_err(|e| format!("Failed to lock chat state: {}", e))?;
+    let flag = flags
+        .get(session_id)
+        .ok_or_else(|| "No chat agent is running for this session".to_string())?;
+    flag.store(true, Ordering::SeqCst);
+    Ok(())
+}
+
+// ============================================================================
+// OUTLINE-FIRST GENERATION
+// ============================================================================
+
+const OUTLINE_SYSTEM_PROMPT: &str = r#"You are an expert educational content creator planning a book of learning material.
+
+Plan the whole book before any of it is written: an ordered list of chapters that covers the topic thoroughly at the requested depth, starting with an introduction/overview, building concepts in a logical order and never covering the same ground twice.
+
+Respond with ONLY a JSON object:
+{
+  "title": "Book title",
+  "description": "One or two sentence description of the book",
+  "chapters": [
+    { "title": "Chapter title", "summary": "What this chapter covers, in two or three sentences" }
+  ]
+}"#;
+
+const OUTLINE_CHAPTER_SYSTEM_PROMPT: &str = r#"You are an expert educational content creator writing one chapter of a book of learning material that follows a fixed outline.
+
+Write the chapter you are asked for, covering exactly what its summary describes. Don't cover material that belongs to other chapters in the outline; refer to them instead where useful. Use clear explanations, examples and markdown formatting (headings, lists, code blocks where relevant).
+
+Respond with ONLY the chapter in markdown, starting with its # heading. No commentary before or after it."#;
+
+/// Event payload carrying the planned chapters in outline-first mode
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AgentOutlineEvent {
+    pub project_id: String,
+    pub outline: BookOutline,
+}
+
+/// Generate a book by planning a chapter outline first, then writing exactly
+/// one page per planned chapter. Gives more even coverage than the
+/// free-running agent and a known chapter count up front.
+pub async fn generate_outlined_material(
+    topic: &str,
+    depth: Depth,
+    language: Option<&str>,
+    target_words_per_chapter: Option<usize>,
+    app: AppHandle,
+) -> Result<ProjectMeta, String> {
+    let mut client = LlmClient::from_config(None)?
+        .with_seed(config_service::get_generation_seed());
+    if !client.has_max_tokens() {
+        client = client.with_max_tokens(Some(GENERATION_MAX_TOKENS));
+    }
+
+    emit_agent_status(&app, "Planning the book...", 0, None);
+
+    let project = create_generation_project(topic, language, target_words_per_chapter)?;
+    let project_id = project.id.clone();
+    let cancel = CancelRegistration::new(generation_cancel_flags(), &project_id);
+    let _ = app.emit("generation-started", &project_id);
 
-    // Initialize agent state
     let mut state = AgentState {
-        project_id: project.id.clone(),
+        project_id: project_id.clone(),
         pages: Vec::new(),
         book_title: None,
         is_finished: false,
         iteration: 0,
-        max_iterations: 30, // Safety limit
+        max_iterations: config_service::get_max_generation_iterations(),
+        total_tokens: 0,
+        outline: None,
     };
 
-    // Build initial user message
-    let initial_prompt = format!(
-        "Create comprehensive learning material about: {}\n\nDepth level: {}\n\nStart by creating the first chapter (introduction/overview). Then continue creating chapters until you have covered the topic thoroughly at the specified depth level. Call the finish tool when done.",
-        topic, depth
-    );
+    // Plan
+    let messages = vec![
+        LlmClient::system_message(&project_system_prompt(OUTLINE_SYSTEM_PROMPT, &project)),
+        LlmClient::user_message(&format!("Topic: {}\n\nDepth level: {}\n{}", topic, depth, depth.guidance())),
+    ];
+    let completion = client.chat_completion_full(messages, Some(0.4)).await?;
+    if let Some(usage) = completion.usage {
+        state.total_tokens += u64::from(usage.total_tokens);
+    }
+
+    let outline: BookOutline = parse_json_object(&completion.content)
+        .filter(|o: &BookOutline| !o.chapters.is_empty())
+        .ok_or("Could not read a chapter outline from the model's response")?;
+
+    let book_info = ToolCall {
+        name: "set_book_info".to_string(),
+        arguments: serde_json::json!({ "title": outline.title, "description": outline.description }),
+    };
+    execute_tool(&book_info, &mut state);
+
+    let _ = app.emit("agent-outline", AgentOutlineEvent {
+        project_id: project_id.clone(),
+        outline: outline.clone(),
+    });
+    state.outline = Some(outline.clone());
+
+    let outline_text = outline.chapters.iter()
+        .enumerate()
+        .map(|(i, c)| format!("{}. {} - {}", i + 1, c.title, c.summary))
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    // Write
+    let total = outline.chapters.len();
+    for (index, chapter) in outline.chapters.iter().enumerate() {
+        if cancel.is_cancelled() {
+            return finish_generation_run(&app, &project_id, &state, "Cancelled by user");
+        }
+
+        state.iteration += 1;
+        emit_generation_status(
+            &app,
+            &format!("Creating: {} ({}/{})", chapter.title, index + 1, total),
+            &state,
+            Some("create_file"),
+        );
+
+        let prompt = format!(
+            "Book: {}\nDepth level: {}\n{}\n\nOutline:\n{}\n\nWrite chapter {}: {}\nIt should cover: {}",
+            outline.title, depth, depth.guidance(), outline_text, index + 1, chapter.title, chapter.summary
+        );
+        let mut messages = vec![
+            LlmClient::system_message(&project_system_prompt(OUTLINE_CHAPTER_SYSTEM_PROMPT, &project)),
+            LlmClient::user_message(&prompt),
+        ];
+        let completion = client.chat_completion_full(messages.clone(), Some(config_service::get_temperature())).await?;
+        if let Some(usage) = completion.usage {
+            state.total_tokens += u64::from(usage.total_tokens);
+        }
+        let mut content = strip_markdown_fence(&completion.content);
+
+        // Models tend to ignore length guidance, so ask once more for a chapter
+        // that came back far too short
+        if let Some(words) = short_chapter_words(&content, target_words_per_chapter) {
+            messages.push(LlmClient::assistant_message(&completion.content));
+            messages.push(LlmClient::user_message(&format!(
+                "This chapter is only {} words, well under the target of roughly {} words. Expand it with more explanation, examples and detail, and respond with the full revised chapter only.",
+                words,
+                target_words_per_chapter.unwrap_or_default()
+            )));
+            let retry = client.chat_completion_full(messages, Some(config_service::get_temperature())).await?;
+            if let Some(usage) = retry.usage {
+                state.total_tokens += u64::from(usage.total_tokens);
+            }
+            let expanded = strip_markdown_fence(&retry.content);
+            if expanded.split_whitespace().count() > words {
+                content = expanded;
+            }
+        }
+
+        let create_file = ToolCall {
+            name: "create_file".to_string(),
+            arguments: serde_json::json!({
+                "title": chapter.title,
+                "content": content,
+            }),
+        };
+        let result = execute_tool(&create_file, &mut state);
+        if !result.success {
+            return Err(result.output);
+        }
+    }
+
+    state.is_finished = true;
+    emit_generation_status(&app, "Content generation complete!", &state, Some("finish"));
+
+    let summary = format!("Used {} tokens", state.total_tokens);
+    finish_generation_run(&app, &project_id, &state, &summary)
+}
+
+/// Run the tool-using generation agent on an existing project until it
+/// calls finish or hits the iteration limit
+async fn run_generation_agent(
+    client: LlmClient,
+    project_id: &str,
+    initial_prompt: &str,
+    app: &AppHandle,
+) -> Result<ProjectMeta, String> {
+    // Let the agent know it can rely on search results when grounding is on
+    let initial_prompt = if client.web_search_enabled() {
+        format!("{}\n\nWeb search is available: use it to check facts and keep content current, and cite sources as markdown links where you rely on them.", initial_prompt)
+    } else {
+        initial_prompt.to_string()
+    };
+
+    let project = load_project(project_id)?;
+    let checkpoint = GenerationCheckpoint {
+        messages: vec![
+            LlmClient::system_message(&project_system_prompt(AGENT_SYSTEM_PROMPT, &project)),
+            LlmClient::user_message(&initial_prompt),
+        ],
+        iteration: 0,
+        pages: Vec::new(),
+        book_title: None,
+        total_tokens: 0,
+    };
+
+    run_agent_loop(client, project_id, checkpoint, app).await
+}
+
+/// Continue a generation that was interrupted (crash, sleep, network error)
+/// from its last saved iteration
+pub async fn resume_generation(project_id: &str, app: AppHandle) -> Result<ProjectMeta, String> {
+    let checkpoint = load_generation_checkpoint(project_id)?
+        .ok_or_else(|| "No interrupted generation to resume for this project".to_string())?;
+
+    let client = LlmClient::from_config(Some(project_id))?;
+    emit_agent_status(&app, "Resuming generation...", checkpoint.iteration, None);
+
+    run_agent_loop(client, project_id, checkpoint, &app).await
+}
+
+/// The agent loop shared by fresh and resumed generations. Progress is
+/// checkpointed to the project dir before every iteration.
+async fn run_agent_loop(
+    mut client: LlmClient,
+    project_id: &str,
+    checkpoint: GenerationCheckpoint,
+    app: &AppHandle,
+) -> Result<ProjectMeta, String> {
+    let app = app.clone();
+
+    // Whole chapters go out in a single create_file call, so don't let a small
+    // provider default cut them off unless the user chose a limit
+    if !client.has_max_tokens() {
+        client = client.with_max_tokens(Some(GENERATION_MAX_TOKENS));
+    }
+    client = client
+        .with_seed(config_service::get_generation_seed())
+        .with_stop(tool_call_stop());
+
+    // Register for cancellation and tell the frontend which project to cancel
+    let cancel = CancelRegistration::new(generation_cancel_flags(), project_id);
+    let _ = app.emit("generation-started", project_id);
+
+    // Switch to the fallback model (once) if the primary keeps failing the tool format
+    let config = config_service::load_config().unwrap_or_default();
+    let mut fallback_model = config.fallback_model.filter(|m| !m.trim().is_empty());
+    let fallback_after = config.fallback_after_failures
+        .unwrap_or(config_service::DEFAULT_FALLBACK_AFTER_FAILURES)
+        .max(1);
+    let mut consecutive_parse_failures = 0;
+
+    // Initialize agent state
+    let mut state = AgentState {
+        project_id: project_id.to_string(),
+        pages: checkpoint.pages,
+        book_title: checkpoint.book_title,
+        is_finished: false,
+        iteration: checkpoint.iteration,
+        max_iterations: config_service::get_max_generation_iterations(), // Safety limit
+        total_tokens: checkpoint.total_tokens,
+        outline: None,
+    };
 
     // Message history for the agent
-    let mut messages = vec![
-        LlmClient::system_message(AGENT_SYSTEM_PROMPT),
-        LlmClient::user_message(&initial_prompt),
-    ];
+    let mut messages = checkpoint.messages;
+    let context_budget = agent_context_budget();
+
+    // Chapter length target, for nudging the agent when a chapter comes back short
+    let target_words = load_project(project_id).ok().and_then(|p| p.target_words_per_chapter);
 
     // Agent loop
     while !state.is_finished && state.iteration < state.max_iterations {
+        if cancel.is_cancelled() {
+            return finish_generation_run(&app, project_id, &state, "Cancelled by user");
+        }
+
+        // Save progress so an interrupted run can be resumed from here
+        if let Err(e) = save_generation_checkpoint(project_id, &messages, &state) {
+            eprintln!("Failed to save generation state: {}", e);
+        }
+
         state.iteration += 1;
 
-        // Call the LLM
-        let response = client.chat_completion(messages.clone(), Some(0.7)).await?;
+        // Keep the growing history inside the model's context window
+        trim_agent_messages(&mut messages, context_budget);
+
+        // Call the LLM, streaming partial output to the frontend
+        let iteration = state.iteration;
+        let completion = client
+            .chat_completion_stream(messages.clone(), Some(config_service::get_temperature()), |token| {
+                let _ = app.emit("agent-token", AgentTokenEvent {
+                    token: token.to_string(),
+                    iteration,
+                });
+            })
+            .await?;
+        if let Some(usage) = completion.usage {
+            state.total_tokens += u64::from(usage.total_tokens);
+        }
+        let response = close_tool_call(completion.content);
+
+        // Keep any web search sources as project references
+        if !completion.citations.is_empty() {
+            if let Err(e) = record_references(project_id, completion.citations) {
+                eprintln!("Failed to save references: {}", e);
+            }
+        }
 
-        // Extract and emit agent's thinking (if any)
-        if let Some(thinking) = extract_agent_thinking(&response) {
-            emit_agent_status(&app, &thinking, state.iteration, None);
+        // Extract and emit agent's thinking (if any). Reasoning models report it
+        // separately, and only the visible content is parsed for the tool call.
+        let thinking = completion.reasoning.as_deref()
+            .and_then(summarize_reasoning)
+            .or_else(|| extract_agent_thinking(&response));
+        if let Some(thinking) = thinking {
+            emit_generation_status(&app, &thinking, &state, None);
         }
 
         // Add assistant response to history
@@ -549,8 +1265,25 @@ pub async fn generate_learning_material(
 
         // Parse tool call from response
         let tool_call = match parse_tool_call(&response) {
-            Ok(tc) => tc,
+            Ok(tc) => {
+                consecutive_parse_failures = 0;
+                tc
+            }
             Err(e) => {
+                consecutive_parse_failures += 1;
+                if consecutive_parse_failures >= fallback_after {
+                    if let Some(model) = fallback_model.take() {
+                        emit_generation_status(
+                            &app,
+                            &format!("Switching to fallback model: {}", model),
+                            &state,
+                            None,
+                        );
+                        client = client.with_model(&model);
+                        consecutive_parse_failures = 0;
+                    }
+                }
+
                 // If parsing fails, add error message and continue
                 let error_msg = format!("Error parsing your response: {}. Please respond with a valid tool call.", e);
                 messages.push(LlmClient::user_message(&error_msg));
@@ -588,22 +1321,36 @@ pub async fn generate_learning_material(
             "finish" => "Finalizing content...".to_string(),
             _ => format!("Executing: {}", tool_call.name),
         };
-        emit_agent_status(&app, &tool_status, state.iteration, Some(&tool_call.name));
+        emit_generation_status(&app, &tool_status, &state, Some(&tool_call.name));
 
         // Execute the tool
         let result = execute_tool(&tool_call, &mut state);
 
         // Add tool result to message history
-        let result_msg = if result.success {
+        let mut result_msg = if result.success {
             format!("Tool '{}' executed successfully:\n{}", result.tool_name, result.output)
         } else {
             format!("Tool '{}' failed:\n{}", result.tool_name, result.output)
         };
-        messages.push(LlmClient::user_message(&result_msg));
+
+        // A chapter well under the length target gets one request to expand it
+        if tool_call.name == "create_file" && result.success {
+            let content = tool_call.arguments.get("content")
+                .and_then(|v| v.as_str())
+                .unwrap_or("");
+            if let Some(words) = short_chapter_words(content, target_words) {
+                result_msg.push_str(&format!(
+                    "\n\nThis chapter is only {} words, well under the target of roughly {} words. Use edit_file to expand it with more explanation, examples and detail before moving on.",
+                    words,
+                    target_words.unwrap_or_default()
+                ));
+            }
+        }
+        messages.push(LlmClient::user_message(&result_msg));
 
         // If finished, break the loop
         if state.is_finished {
-            emit_agent_status(&app, "Content generation complete!", state.iteration, Some("finish"));
+            emit_generation_status(&app, "Content generation complete!", &state, Some("finish"));
             break;
         }
     }
@@ -611,11 +1358,642 @@ pub async fn generate_learning_material(
     // If we hit max iterations without finishing, that's okay - we likely have content
     if state.iteration >= state.max_iterations && !state.is_finished {
         eprintln!("Agent reached max iterations ({}) without calling finish", state.max_iterations);
-        emit_agent_status(&app, "Wrapping up...", state.iteration, None);
+        emit_generation_status(&app, "Wrapping up...", &state, None);
+    }
+
+    let summary = format!("Used {} tokens", state.total_tokens);
+    finish_generation_run(&app, project_id, &state, &summary)
+}
+
+/// Record the run's token usage on the project, send the final status event
+/// and return the reloaded project (with its updated page order)
+fn finish_generation_run(
+    app: &AppHandle,
+    project_id: &str,
+    state: &AgentState,
+    message: &str,
+) -> Result<ProjectMeta, String> {
+    let mut project = load_project(project_id)?;
+    project.total_tokens += state.total_tokens;
+    crate::services::file_service::save_project(&project)?;
+
+    // The run ended cleanly, so there's nothing left to resume
+    if let Err(e) = clear_generation_checkpoint(project_id) {
+        eprintln!("Failed to remove generation state: {}", e);
+    }
+
+    let _ = app.emit("agent-status", AgentStatusEvent {
+        message: message.to_string(),
+        iteration: state.iteration,
+        tool_name: None,
+        total_tokens: Some(state.total_tokens),
+        chapters_done: Some(state.pages.len()),
+        chapters_total: state.outline.as_ref().map(|o| o.chapters.len()),
+    });
+
+    Ok(project)
+}
+
+/// Everything needed to pick a generation back up after an interruption,
+/// stored as `generation-state.json` in the project dir
+#[derive(Debug, Serialize, Deserialize)]
+struct GenerationCheckpoint {
+    messages: Vec<crate::services::llm_client::ChatMessage>,
+    iteration: u32,
+    pages: Vec<PageInfo>,
+    book_title: Option<String>,
+    total_tokens: u64,
+}
+
+fn generation_checkpoint_path(project_id: &str) -> Result<std::path::PathBuf, String> {
+    Ok(crate::services::file_service::get_project_dir(project_id)?.join("generation-state.json"))
+}
+
+fn save_generation_checkpoint(
+    project_id: &str,
+    messages: &[crate::services::llm_client::ChatMessage],
+    state: &AgentState,
+) -> Result<(), String> {
+    let checkpoint = GenerationCheckpoint {
+        messages: messages.to_vec(),
+        iteration: state.iteration,
+        pages: state.pages.clone(),
+        book_title: state.book_title.clone(),
+        total_tokens: state.total_tokens,
+    };
+    let content = serde_json::to_string(&checkpoint)
+        .map_err(|e| format!("Failed to serialize generation state: {}", e))?;
+    crate::services::file_service::write_atomic(&generation_checkpoint_path(project_id)?, content)
+        .map_err(|e| format!("Failed to write generation state: {}", e))
+}
+
+fn load_generation_checkpoint(project_id: &str) -> Result<Option<GenerationCheckpoint>, String> {
+    let path = generation_checkpoint_path(project_id)?;
+    if !path.exists() {
+        return Ok(None);
+    }
+    let content = std::fs::read_to_string(&path)
+        .map_err(|e| format!("Failed to read generation state: {}", e))?;
+    serde_json::from_str(&content)
+        .map(Some)
+        .map_err(|e| format!("Failed to parse generation state: {}", e))
+}
+
+fn clear_generation_checkpoint(project_id: &str) -> Result<(), String> {
+    let path = generation_checkpoint_path(project_id)?;
+    if path.exists() {
+        std::fs::remove_file(&path)
+            .map_err(|e| format!("Failed to remove generation state: {}", e))?;
+    }
+    Ok(())
+}
+
+/// Add newly cited sources to the project's reference list
+fn record_references(project_id: &str, citations: Vec<Citation>) -> Result<(), String> {
+    let mut project = load_project(project_id)?;
+    for citation in citations {
+        if !project.references.iter().any(|r| r.url == citation.url) {
+            project.references.push(citation);
+        }
+    }
+    crate::services::file_service::save_project(&project)
+}
+
+// ============================================================================
+// OBJECTIVE VERIFICATION
+// ============================================================================
+
+const OBJECTIVES_SYSTEM_PROMPT: &str = r#"You are reviewing a book of learning material against its learning objectives. For each objective, decide whether the book teaches it well enough that a reader could achieve it.
+
+Respond with ONLY a JSON array, one entry per objective, in the same order:
+[
+  {
+    "objective": "The objective text",
+    "covered": true,
+    "evidence": "Which chapter or section covers it, or what is missing"
+  }
+]"#;
+
+/// Check whether each of a project's learning objectives is addressed by its pages
+pub async fn verify_objectives_covered(project_id: &str) -> Result<Vec<ObjectiveCoverage>, String> {
+    let project = load_project(project_id)?;
+    if project.objectives.is_empty() {
+        return Err("This project has no learning objectives".to_string());
+    }
+
+    let book = load_book_text(&project)?;
+
+    let client = LlmClient::from_config(Some(project_id))?;
+
+    let objective_list: Vec<String> = project.objectives.iter()
+        .enumerate()
+        .map(|(i, o)| format!("{}. {}", i + 1, o))
+        .collect();
+    let user_prompt = format!(
+        "## Learning Objectives\n{}\n\n## Book\n{}",
+        objective_list.join("\n"), book
+    );
+
+    let messages = vec![
+        LlmClient::system_message(OBJECTIVES_SYSTEM_PROMPT),
+        LlmClient::user_message(&user_prompt),
+    ];
+
+    let response = client.chat_completion(messages, Some(0.2)).await?;
+
+    match parse_json_array::<ObjectiveCoverage>(&response) {
+        Some(coverage) if coverage.len() == project.objectives.len() => Ok(coverage),
+        // Fall back to keyword matching if the model didn't return usable JSON
+        _ => Ok(keyword_objective_coverage(&project.objectives, &book)),
+    }
+}
+
+/// Concatenate every page of a project, labelled by filename, for use as LLM context
+fn load_book_text(project: &ProjectMeta) -> Result<String, String> {
+    let mut book = String::new();
+    for page_name in &project.page_order {
+        let content = load_page_content(&project.id, page_name)?;
+        book.push_str(&format!("## File: {}\n\n{}\n\n", page_name, content));
+    }
+    Ok(book)
+}
+
+/// Extract and parse the outermost JSON object from an LLM response
+fn parse_json_object<T: serde::de::DeserializeOwned>(response: &str) -> Option<T> {
+    let start = response.find('{')?;
+    let end = response.rfind('}')?;
+    if end < start {
+        return None;
+    }
+    serde_json::from_str(&response[start..=end]).ok()
+}
+
+/// Extract and parse the outermost JSON array from an LLM response
+fn parse_json_array<T: serde::de::DeserializeOwned>(response: &str) -> Option<Vec<T>> {
+    let start = response.find('[')?;
+    let end = response.rfind(']')?;
+    if end < start {
+        return None;
+    }
+    serde_json::from_str(&response[start..=end]).ok()
+}
+
+/// Treat an objective as covered if most of its significant words appear in the book
+fn keyword_objective_coverage(objectives: &[String], book: &str) -> Vec<ObjectiveCoverage> {
+    let book_lower = book.to_lowercase();
+
+    objectives.iter()
+        .map(|objective| {
+            let keywords: Vec<String> = objective
+                .split(|c: char| !c.is_alphanumeric())
+                .filter(|w| w.chars().count() > 4)
+                .map(|w| w.to_lowercase())
+                .collect();
+            let found = keywords.iter().filter(|k| book_lower.contains(k.as_str())).count();
+            let covered = !keywords.is_empty() && found * 2 >= keywords.len();
+
+            ObjectiveCoverage {
+                objective: objective.clone(),
+                covered,
+                evidence: Some(format!("Keyword check: {} of {} key terms found", found, keywords.len())),
+            }
+        })
+        .collect()
+}
+
+// ============================================================================
+// COMPLETENESS ASSESSMENT
+// ============================================================================
+
+const COMPLETENESS_SYSTEM_PROMPT: &str = r#"You are reviewing a book of learning material for completeness. Judge whether it adequately covers its stated topic (and learning objectives, if given) for a reader who wants to learn the subject. Focus on missing topics, not on style or readability.
+
+Respond with ONLY a JSON object:
+{
+  "score": 0-100,
+  "summary": "One or two sentences on how thorough the book is",
+  "missingTopics": ["A topic the book should cover but doesn't", "..."]
+}"#;
+
+/// Ask the LLM how thoroughly a project covers its topic and what is missing
+pub async fn assess_completeness(project_id: &str) -> Result<CompletenessAssessment, String> {
+    let project = load_project(project_id)?;
+    if project.page_order.is_empty() {
+        return Err("This project has no pages to assess".to_string());
+    }
+
+    let book = load_book_text(&project)?;
+    let client = LlmClient::from_config(Some(project_id))?;
+
+    let mut user_prompt = format!("## Title\n{}\n\n", project.title);
+    if !project.description.is_empty() {
+        user_prompt.push_str(&format!("## Description\n{}\n\n", project.description));
+    }
+    if !project.objectives.is_empty() {
+        user_prompt.push_str(&format!("## Learning Objectives\n- {}\n\n", project.objectives.join("\n- ")));
+    }
+    user_prompt.push_str(&format!("## Book\n{}", book));
+
+    let messages = vec![
+        LlmClient::system_message(COMPLETENESS_SYSTEM_PROMPT),
+        LlmClient::user_message(&user_prompt),
+    ];
+
+    let response = client.chat_completion(messages, Some(0.2)).await?;
+
+    let mut assessment: CompletenessAssessment = parse_json_object(&response)
+        .ok_or("Could not read the completeness assessment from the model's response")?;
+    assessment.score = assessment.score.min(100);
+
+    Ok(assessment)
+}
+
+// ============================================================================
+// SERIES GENERATION
+// ============================================================================
+
+/// Event payload for per-module progress while generating a series
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SeriesProgressEvent {
+    pub series_title: String,
+    pub module_index: usize,
+    pub module_total: usize,
+    pub topic: String,
+    pub status: String,
+    pub error: Option<String>,
+}
+
+fn emit_series_progress(
+    app: &AppHandle,
+    series_title: &str,
+    module_index: usize,
+    module_total: usize,
+    topic: &str,
+    status: &str,
+    error: Option<String>,
+) {
+    let _ = app.emit("series-progress", SeriesProgressEvent {
+        series_title: series_title.to_string(),
+        module_index,
+        module_total,
+        topic: topic.to_string(),
+        status: status.to_string(),
+        error,
+    });
+}
+
+/// Generate one book per module topic, one after another, and tag each with
+/// the series title so `list_projects_by_tag` finds the whole series. A
+/// failed module is reported and skipped so the rest of
+/// the series still gets generated.
+pub async fn generate_series(
+    series_title: &str,
+    topics: &[String],
+    depth: Depth,
+    api_key: &str,
+    app: AppHandle,
+) -> Result<Vec<ProjectMeta>, String> {
+    let mut projects = Vec::new();
+
+    for (index, topic) in topics.iter().enumerate() {
+        let module_index = index + 1;
+        emit_series_progress(&app, series_title, module_index, topics.len(), topic, "started", None);
+
+        // Run sequentially to stay within provider rate limits
+        let result = generate_learning_material(topic, depth, None, None, api_key, app.clone())
+            .await
+            .and_then(|project| {
+                let mut tags = project.tags.clone();
+                tags.push(series_title.to_string());
+                crate::services::file_service::set_project_tags(&project.id, tags)
+            });
+
+        match result {
+            Ok(project) => {
+                emit_series_progress(&app, series_title, module_index, topics.len(), topic, "completed", None);
+                projects.push(project);
+            }
+            Err(e) => {
+                eprintln!("Series module '{}' failed: {}", topic, e);
+                emit_series_progress(&app, series_title, module_index, topics.len(), topic, "failed", Some(e));
+            }
+        }
+    }
+
+    Ok(projects)
+}
+
+// ============================================================================
+// PAGE REGENERATION
+// ============================================================================
+
+const REGENERATE_PAGE_SYSTEM_PROMPT: &str = r#"You are an expert educational content writer revising one chapter of a book of learning material.
+
+Rewrite the chapter you are given, following the user's instructions. Keep it consistent with the neighbouring chapters: don't repeat what they cover and keep the same terminology, tone and depth.
+
+Respond with ONLY the complete rewritten chapter in markdown, starting with its # heading. No commentary before or after it."#;
+
+/// How much of each neighbouring chapter to include as context
+const NEIGHBOR_CONTEXT_CHARS: usize = 6000;
+
+/// Rewrite a single chapter following the user's instructions, using the
+/// chapters before and after it as context. The page keeps its filename and
+/// position. Returns the new content.
+pub async fn regenerate_page(
+    project_id: &str,
+    page_name: &str,
+    instructions: &str,
+    app: AppHandle,
+) -> Result<String, String> {
+    let project = load_project(project_id)?;
+    let index = project.page_order.iter()
+        .position(|p| p == page_name)
+        .ok_or_else(|| format!("Page '{}' not found in project", page_name))?;
+
+    let client = LlmClient::from_config(Some(project_id))?;
+    emit_agent_status(&app, "Reading surrounding chapters...", 1, None);
+
+    let current = load_page_content(project_id, page_name)?;
+    let neighbor = |i: Option<usize>| -> Option<String> {
+        let name = project.page_order.get(i?)?;
+        let content = load_page_content(project_id, name).ok()?;
+        Some(content.chars().take(NEIGHBOR_CONTEXT_CHARS).collect())
+    };
+
+    let mut user_prompt = format!("## Book\n{}\n\n", project.title);
+    if let Some(previous) = neighbor(index.checked_sub(1)) {
+        user_prompt.push_str(&format!("## Previous chapter (for context)\n{}\n\n", previous));
+    }
+    if let Some(next) = neighbor(Some(index + 1)) {
+        user_prompt.push_str(&format!("## Next chapter (for context)\n{}\n\n", next));
+    }
+    user_prompt.push_str(&format!("## Chapter to rewrite\n{}\n\n", current));
+    let instructions = if instructions.trim().is_empty() {
+        "Improve the chapter's clarity, depth and examples."
+    } else {
+        instructions.trim()
+    };
+    user_prompt.push_str(&format!("## Instructions\n{}", instructions));
+
+    let messages = vec![
+        LlmClient::system_message(&project_system_prompt(REGENERATE_PAGE_SYSTEM_PROMPT, &project)),
+        LlmClient::user_message(&user_prompt),
+    ];
+
+    emit_agent_status(&app, "Rewriting chapter...", 2, None);
+    let response = client.chat_completion(messages, Some(config_service::get_temperature())).await?;
+
+    let content = strip_markdown_fence(&response);
+    if content.is_empty() {
+        return Err("The model returned an empty chapter".to_string());
+    }
+
+    save_page_content(project_id, page_name, &content)?;
+    emit_agent_status(&app, "Chapter rewritten", 3, None);
+
+    Ok(content)
+}
+
+const CONTINUE_PAGE_SYSTEM_PROMPT: &str = r#"You are an expert educational content writer extending one chapter of a book of learning material.
+
+Continue the chapter from exactly where it ends, following the user's guidance if given. The new text must flow naturally from the last paragraph: same terminology, tone, depth and formatting. Don't repeat, summarize or rewrite anything already in the chapter, and don't cover what the next chapter covers.
+
+Respond with ONLY the new markdown to append - no chapter heading, no commentary before or after it."#;
+
+/// Extend a chapter by appending new prose after its current content, using
+/// the chapter after it as context. Existing text is never changed. Returns
+/// the appended text.
+pub async fn continue_page(
+    project_id: &str,
+    page_name: &str,
+    guidance: &str,
+    app: AppHandle,
+) -> Result<String, String> {
+    let project = load_project(project_id)?;
+    let index = project.page_order.iter()
+        .position(|p| p == page_name)
+        .ok_or_else(|| format!("Page '{}' not found in project", page_name))?;
+
+    let client = LlmClient::from_config(Some(project_id))?;
+    emit_agent_status(&app, "Reading chapter...", 1, None);
+
+    let current = load_page_content(project_id, page_name)?;
+    let current = current.trim_end();
+
+    let mut user_prompt = format!("## Book\n{}\n\n", project.title);
+    if let Some(next) = project.page_order.get(index + 1)
+        .and_then(|name| load_page_content(project_id, name).ok())
+    {
+        let next: String = next.chars().take(NEIGHBOR_CONTEXT_CHARS).collect();
+        user_prompt.push_str(&format!("## Next chapter (for context)\n{}\n\n", next));
+    }
+    user_prompt.push_str(&format!("## Chapter to continue\n{}\n\n", current));
+    let guidance = if guidance.trim().is_empty() {
+        "Continue naturally, deepening the chapter's topic with further explanation and examples."
+    } else {
+        guidance.trim()
+    };
+    user_prompt.push_str(&format!("## Guidance\n{}", guidance));
+
+    let messages = vec![
+        LlmClient::system_message(&project_system_prompt(CONTINUE_PAGE_SYSTEM_PROMPT, &project)),
+        LlmClient::user_message(&user_prompt),
+    ];
+
+    emit_agent_status(&app, "Writing continuation...", 2, None);
+    let response = client.chat_completion(messages, Some(config_service::get_temperature())).await?;
+
+    // Keep only new text if the model echoed the chapter (or its heading) back
+    let mut addition = strip_markdown_fence(&response);
+    if let Some(rest) = addition.strip_prefix(current) {
+        addition = rest.trim().to_string();
+    }
+    if let Some(title) = extract_page_title(current) {
+        if let Some(rest) = addition.strip_prefix(&format!("# {}", title)) {
+            addition = rest.trim().to_string();
+        }
+    }
+    if addition.is_empty() {
+        return Err("The model didn't write any new content".to_string());
+    }
+
+    save_page_content(project_id, page_name, &format!("{}\n\n{}\n", current, addition))?;
+    emit_agent_status(&app, "Chapter extended", 3, None);
+
+    Ok(addition)
+}
+
+/// Unwrap a response the model put inside a ```markdown fence anyway
+fn strip_markdown_fence(response: &str) -> String {
+    let trimmed = response.trim();
+    let Some(rest) = trimmed.strip_prefix("```") else {
+        return trimmed.to_string();
+    };
+    let Some(body) = rest.strip_suffix("```") else {
+        return trimmed.to_string();
+    };
+    // Drop the language tag on the opening fence line
+    match body.split_once('\n') {
+        Some((_, inner)) => inner.trim().to_string(),
+        None => body.trim().to_string(),
+    }
+}
+
+// ============================================================================
+// QUIZ GENERATION
+// ============================================================================
+
+const QUIZ_SYSTEM_PROMPT: &str = r#"You are an expert educator writing a multiple-choice quiz that checks understanding of one chapter of learning material.
+
+Ask about the chapter's key ideas, not trivia. Each question has exactly one correct choice and plausible wrong ones.
+
+Respond with ONLY a JSON object:
+{
+  "questions": [
+    {
+      "question": "Question text",
+      "choices": ["Choice A", "Choice B", "Choice C", "Choice D"],
+      "answerIndex": 0,
+      "explanation": "Why the correct choice is right"
+    }
+  ]
+}"#;
+
+/// Write a multiple-choice quiz for a page and append it to the page as a
+/// markdown section. Returns the quiz so the UI can also render it interactively.
+pub async fn generate_quiz(project_id: &str, page_name: &str, num_questions: usize) -> Result<Quiz, String> {
+    let num_questions = num_questions.clamp(1, 20);
+    let content = load_page_content(project_id, page_name)?;
+    let client = LlmClient::from_config(Some(project_id))?;
+
+    let messages = vec![
+        LlmClient::system_message(QUIZ_SYSTEM_PROMPT),
+        LlmClient::user_message(&format!(
+            "Write {} questions for this chapter:\n\n{}",
+            num_questions, content
+        )),
+    ];
+
+    let response = client.chat_completion(messages, Some(0.4)).await?;
+
+    let mut quiz: Quiz = parse_json_object(&response)
+        .ok_or("Could not read the quiz from the model's response")?;
+    quiz.questions.retain(|q| q.answer_index < q.choices.len());
+    if quiz.questions.is_empty() {
+        return Err("The model didn't return any usable quiz questions".to_string());
+    }
+
+    let updated = format!("{}\n\n{}", content.trim_end(), quiz_to_markdown(&quiz));
+    save_page_content(project_id, page_name, &updated)?;
+
+    Ok(quiz)
+}
+
+/// Render a quiz as a markdown section, with each answer folded away
+fn quiz_to_markdown(quiz: &Quiz) -> String {
+    let mut markdown = String::from("## Quiz\n");
+
+    for (i, question) in quiz.questions.iter().enumerate() {
+        markdown.push_str(&format!("\n**{}. {}**\n\n", i + 1, question.question));
+        for (j, choice) in question.choices.iter().enumerate() {
+            markdown.push_str(&format!("- {}) {}\n", choice_letter(j), choice));
+        }
+
+        let answer = &question.choices[question.answer_index];
+        markdown.push_str(&format!(
+            "\n<details class=\"quiz-answer\"><summary>Answer</summary>\n\n{}) {}",
+            choice_letter(question.answer_index),
+            answer
+        ));
+        if !question.explanation.is_empty() {
+            markdown.push_str(&format!(" - {}", question.explanation));
+        }
+        markdown.push_str("\n\n</details>\n");
+    }
+
+    markdown
+}
+
+fn choice_letter(index: usize) -> char {
+    (b'A' + (index % 26) as u8) as char
+}
+
+// ============================================================================
+// GLOSSARY GENERATION
+// ============================================================================
+
+const GLOSSARY_SYSTEM_PROMPT: &str = r#"You are an expert educator compiling the glossary for a book of learning material.
+
+Pick the key technical terms a reader needs to know, as used in the book. Define each in one or two plain sentences, consistent with how the book uses it. Skip everyday words.
+
+Respond with ONLY a JSON array:
+[
+  { "term": "Term", "definition": "Concise definition" }
+]"#;
+
+/// Whether a page is the one `generate_glossary` writes
+fn is_glossary_page(page_name: &str) -> bool {
+    let stem = page_name.trim_end_matches(".md").trim_start_matches(|c: char| c.is_ascii_digit());
+    stem.strip_prefix('-').is_some_and(|rest| {
+        rest == "glossary" || rest.strip_prefix("glossary-").is_some_and(|n| n.parse::<u32>().is_ok())
+    })
+}
+
+/// Ask the LLM for the book's key terms and write them to a Glossary page at
+/// the end of the book, replacing the page from an earlier run if there is one.
+/// Returns the entries so the UI can also present them interactively.
+pub async fn generate_glossary(project_id: &str) -> Result<Vec<GlossaryEntry>, String> {
+    let project = load_project(project_id)?;
+    let existing = project.page_order.iter().find(|p| is_glossary_page(p)).cloned();
+
+    let mut book = String::new();
+    for page_name in project.page_order.iter().filter(|p| !is_glossary_page(p)) {
+        let content = load_page_content(project_id, page_name)?;
+        book.push_str(&format!("## File: {}\n\n{}\n\n", page_name, content));
     }
+    if book.is_empty() {
+        return Err("This project has no pages to build a glossary from".to_string());
+    }
+
+    let client = LlmClient::from_config(Some(project_id))?;
+    let messages = vec![
+        LlmClient::system_message(&project_system_prompt(GLOSSARY_SYSTEM_PROMPT, &project)),
+        LlmClient::user_message(&format!("Build the glossary for this book:\n\n{}", book)),
+    ];
 
-    // Reload to get updated page order
-    load_project(&project.id)
+    let response = client.chat_completion(messages, Some(0.2)).await?;
+
+    let mut entries: Vec<GlossaryEntry> = parse_json_array(&response)
+        .ok_or("Could not read the glossary from the model's response")?;
+    entries.retain(|e| !e.term.trim().is_empty() && !e.definition.trim().is_empty());
+    if entries.is_empty() {
+        return Err("The model didn't return any glossary entries".to_string());
+    }
+    entries.sort_by_key(|e| e.term.to_lowercase());
+    entries.dedup_by_key(|e| e.term.to_lowercase());
+
+    let markdown = glossary_to_markdown(&entries);
+    match existing {
+        Some(page_name) => save_page_content(project_id, &page_name, &markdown)?,
+        None => {
+            add_page_to_project(project_id, "Glossary", &markdown)?;
+        }
+    }
+
+    Ok(entries)
+}
+
+/// Render glossary entries as a markdown definition list
+fn glossary_to_markdown(entries: &[GlossaryEntry]) -> String {
+    let mut markdown = String::from("# Glossary\n");
+    for entry in entries {
+        markdown.push_str(&format!(
+            "\n{}\n: {}\n",
+            entry.term.trim(),
+            entry.definition.split_whitespace().collect::<Vec<_>>().join(" ")
+        ));
+    }
+    markdown
 }
 
 // ============================================================================
@@ -953,16 +2331,33 @@ fn find_line_fuzzy(lines: &[String], target: &str) -> Option<usize> {
     None
 }
 
-/// Compute replacements from chunks (Codex-style with fuzzy fallback)
+/// Line edits computed from a set of update chunks
+struct ChunkReplacements {
+    /// (start index, number of lines replaced, new lines), sorted by start
+    replacements: Vec<(usize, usize, Vec<String>)>,
+    /// Set when a chunk couldn't be matched exactly and was placed by guess
+    approximate: bool,
+}
+
+/// Compute replacements from chunks (Codex-style with fuzzy fallback).
+/// When a chunk can't be anchored anywhere it is inserted after
+/// `fallback_line` (the end of the user's selection) if given, otherwise
+/// appended, and the result is marked approximate.
 fn compute_replacements(
     original_lines: &[String],
     chunks: &[UpdateFileChunk],
-) -> Result<Vec<(usize, usize, Vec<String>)>, String> {
+    fallback_line: Option<usize>,
+) -> Result<ChunkReplacements, String> {
     let mut replacements: Vec<(usize, usize, Vec<String>)> = Vec::new();
     let mut line_index: usize = 0;
+    let mut approximate = false;
+    let fallback_idx = fallback_line
+        .map(|line| line.min(original_lines.len()))
+        .unwrap_or(original_lines.len());
 
     for chunk in chunks {
         // If chunk has change_context, find it first
+        let mut context_missed = false;
         if let Some(ctx_line) = &chunk.change_context {
             if let Some(idx) = seek_sequence(
                 original_lines,
@@ -974,15 +2369,24 @@ fn compute_replacements(
             } else if let Some(idx) = find_line_fuzzy(original_lines, ctx_line) {
                 // Fuzzy fallback for context
                 line_index = idx + 1;
+            } else {
+                // If still not found, continue anyway
+                context_missed = true;
             }
-            // If still not found, continue anyway
         }
 
         if chunk.old_lines.is_empty() {
             // Pure addition - add at current position or end
             let insertion_idx = if chunk.is_end_of_file {
                 original_lines.len()
+            } else if context_missed && line_index == 0 {
+                // Nothing anchored it, so don't drop it at the top of the page
+                approximate = true;
+                fallback_idx
             } else {
+                // A missed context still lands after the previous chunk,
+                // which is only a guess
+                approximate |= context_missed;
                 line_index.min(original_lines.len())
             };
             replacements.push((insertion_idx, 0, chunk.new_lines.clone()));
@@ -1030,6 +2434,7 @@ fn compute_replacements(
         // Last resort: if we have context, insert after context position
         if found.is_none() && line_index > 0 {
             // Insert as pure addition after context
+            approximate = true;
             replacements.push((line_index, 0, new_slice.to_vec()));
             continue;
         }
@@ -1038,13 +2443,29 @@ fn compute_replacements(
             replacements.push((start_idx, pattern.len(), new_slice.to_vec()));
             line_index = start_idx + pattern.len();
         } else {
-            // Ultimate fallback: append to end of document
-            replacements.push((original_lines.len(), 0, new_slice.to_vec()));
+            // Ultimate fallback: insert near the selection (or at the end)
+            approximate = true;
+            replacements.push((fallback_idx, 0, new_slice.to_vec()));
         }
     }
 
     replacements.sort_by(|(lhs, _, _), (rhs, _, _)| lhs.cmp(rhs));
-    Ok(replacements)
+    Ok(ChunkReplacements { replacements, approximate })
+}
+
+/// Where `line` ends up once `replacements` are applied, so a later patch
+/// operation still inserts right after the same text. A line inside a
+/// replaced range moves to the end of the new lines.
+fn shift_line(line: usize, replacements: &[(usize, usize, Vec<String>)]) -> usize {
+    let mut shifted = line as isize;
+    for (start_idx, old_len, new_segment) in replacements {
+        if start_idx + old_len <= line {
+            shifted += new_segment.len() as isize - *old_len as isize;
+        } else if *start_idx < line {
+            shifted += (start_idx + new_segment.len()) as isize - line as isize;
+        }
+    }
+    shifted.max(0) as usize
 }
 
 /// Apply replacements to lines (Codex-style - reverse order)
@@ -1083,8 +2504,14 @@ fn apply_replacements(
 }
 
 /// Apply update chunks to content (main entry point)
-/// Returns (updated content, line numbers, inserted content)
-fn apply_update_chunks(original: &str, chunks: &[UpdateFileChunk]) -> Result<(String, Vec<usize>, String), String> {
+/// Returns (updated content, line numbers, inserted content, whether any
+/// chunk was placed at `fallback_line` because it couldn't be matched).
+/// `fallback_line` is moved past the lines inserted or removed before it.
+fn apply_update_chunks(
+    original: &str,
+    chunks: &[UpdateFileChunk],
+    fallback_line: &mut Option<usize>,
+) -> Result<(String, Vec<usize>, String, bool), String> {
     let mut original_lines: Vec<String> = original.split('\n').map(String::from).collect();
 
     // Drop trailing empty element from final newline
@@ -1092,7 +2519,11 @@ fn apply_update_chunks(original: &str, chunks: &[UpdateFileChunk]) -> Result<(St
         original_lines.pop();
     }
 
-    let replacements = compute_replacements(&original_lines, chunks)?;
+    let ChunkReplacements { replacements, approximate } =
+        compute_replacements(&original_lines, chunks, *fallback_line)?;
+    if let Some(line) = fallback_line.as_mut() {
+        *line = shift_line((*line).min(original_lines.len()), &replacements);
+    }
     let (mut new_lines, updated_lines, inserted_content) = apply_replacements(original_lines, &replacements);
 
     // Ensure trailing newline
@@ -1100,28 +2531,154 @@ fn apply_update_chunks(original: &str, chunks: &[UpdateFileChunk]) -> Result<(St
         new_lines.push(String::new());
     }
 
-    Ok((new_lines.join("\n"), updated_lines, inserted_content))
+    Ok((new_lines.join("\n"), updated_lines, inserted_content, approximate))
+}
+
+/// Tokens kept free in the context window for the model's patch output
+const EXPANSION_RESPONSE_RESERVE_TOKENS: usize = 4_096;
+
+/// Rough token estimate (~4 characters per token for English text)
+fn estimate_tokens(text: &str) -> usize {
+    text.chars().count().div_ceil(4)
+}
+
+/// Tokens used by everything in the expansion prompt except the page itself
+fn expansion_prompt_overhead(selection: &SelectionRange, question: &str) -> usize {
+    estimate_tokens(EXPANSION_SYSTEM_PROMPT)
+        + estimate_tokens(&selection.selected_text)
+        + estimate_tokens(question)
+        + 50 // Prompt headings and formatting
+}
+
+/// Estimate whether an expansion prompt for this page fits the configured context limit
+pub fn estimate_expansion_context(
+    project_id: &str,
+    page_name: &str,
+    selection: &SelectionRange,
+    question: &str,
+) -> Result<ContextEstimate, String> {
+    let content = load_page_content(project_id, page_name)?;
+    let context_limit = config_service::get_context_limit()?;
+
+    let estimated_tokens = estimate_tokens(&content) + expansion_prompt_overhead(selection, question);
+
+    Ok(ContextEstimate {
+        estimated_tokens,
+        context_limit,
+        fits: estimated_tokens + EXPANSION_RESPONSE_RESERVE_TOKENS <= context_limit,
+    })
+}
+
+/// Cut the document down to a window of roughly `max_chars` characters
+/// centered on the selection, marking the omitted parts.
+/// Returns None if the selection can't be located or doesn't fit by itself.
+fn truncate_around_selection(content: &str, selection: &SelectionRange, max_chars: usize) -> Option<String> {
+    let (sel_start, sel_end) = match content.find(&selection.selected_text) {
+        Some(start) if !selection.selected_text.is_empty() => (start, start + selection.selected_text.len()),
+        _ => {
+            // Fall back to the selected line range
+            if selection.start_line == 0 {
+                return None;
+            }
+            let line_start = content
+                .split_inclusive('\n')
+                .take(selection.start_line - 1)
+                .map(|l| l.len())
+                .sum::<usize>();
+            if line_start >= content.len() {
+                return None;
+            }
+            (line_start, line_start)
+        }
+    };
+
+    let selection_chars = content[sel_start..sel_end].chars().count();
+    if selection_chars > max_chars {
+        return None;
+    }
+    let half_window = (max_chars - selection_chars) / 2;
+
+    let mut start = content[..sel_start]
+        .char_indices()
+        .rev()
+        .take(half_window)
+        .last()
+        .map(|(i, _)| i)
+        .unwrap_or(sel_start);
+    let mut end = content[sel_end..]
+        .char_indices()
+        .nth(half_window)
+        .map(|(i, _)| sel_end + i)
+        .unwrap_or(content.len());
+
+    // Snap to whole lines so patch context lines stay intact
+    if start > 0 {
+        if let Some(newline) = content[start..sel_start].find('\n') {
+            start += newline + 1;
+        }
+    }
+    if end < content.len() {
+        if let Some(newline) = content[sel_end..end].rfind('\n') {
+            end = sel_end + newline + 1;
+        }
+    }
+
+    let mut excerpt = String::new();
+    if start > 0 {
+        excerpt.push_str("[... earlier content omitted ...]\n");
+    }
+    excerpt.push_str(&content[start..end]);
+    if end < content.len() {
+        excerpt.push_str("\n[... later content omitted ...]");
+    }
+
+    Some(excerpt)
+}
+
+/// A generated expansion: the page content it was applied to, the result,
+/// and any Add/Delete File operations still to be carried out on save
+struct GeneratedExpansion {
+    original_content: String,
+    result: ExpansionResult,
+    file_operations: Vec<PatchOperation>,
 }
 
-/// Expand a selection with AI-generated content using Codex-style patches
-pub async fn expand_selection_with_ai(
+/// Ask the LLM for an expansion patch and apply its page updates in memory.
+/// Nothing is written to disk.
+async fn generate_expansion(
     project_id: &str,
     page_name: &str,
     selection: &SelectionRange,
     question: &str,
-    _api_key: &str,
-) -> Result<ExpansionResult, String> {
-    // Get configuration
+) -> Result<GeneratedExpansion, String> {
     // Create LLM client from config
-    let client = LlmClient::from_config()?;
+    let client = LlmClient::from_config(Some(project_id))?;
 
     // Load the current page content
     let content = load_page_content(project_id, page_name)?;
 
-    // Build the expansion prompt with full document context
+    // Keep the prompt within the model's context window, trimming the page
+    // down to the area around the selection if it is too long
+    let context_limit = config_service::get_context_limit()?;
+    let budget = context_limit.saturating_sub(EXPANSION_RESPONSE_RESERVE_TOKENS);
+    let overhead = expansion_prompt_overhead(selection, question);
+    let estimated_tokens = estimate_tokens(&content) + overhead;
+
+    let document_context = if estimated_tokens <= budget {
+        content.clone()
+    } else {
+        let max_chars = budget.saturating_sub(overhead) * 4;
+        truncate_around_selection(&content, selection, max_chars).ok_or_else(|| format!(
+            "Context too large: this page needs about {} tokens but the context limit is {}. \
+             Select a shorter passage or raise the context limit in Settings.",
+            estimated_tokens, context_limit
+        ))?
+    };
+
+    // Build the expansion prompt with document context
     let user_prompt = format!(
         "## Current Document\n```\n{}\n```\n\n## Selected Text\n\"{}\"\n\n## Question\n{}",
-        content, selection.selected_text, question
+        document_context, selection.selected_text, question
     );
 
     let messages = vec![
@@ -1129,33 +2686,49 @@ pub async fn expand_selection_with_ai(
         LlmClient::user_message(&user_prompt),
     ];
 
-    let response = client.chat_completion(messages, Some(0.7)).await?;
+    let response = client.chat_completion(messages, Some(config_service::get_temperature())).await?;
 
     // Parse the patch from the AI response
     let operations = parse_patch(&response)?;
 
-    // Find UpdateFile operation and apply it
-    let (updated_markdown, updated_lines, inserted_content) = {
-        let mut result_content = content.clone();
-        let mut result_lines = Vec::new();
-        let mut result_inserted = String::new();
-
-        for op in operations {
-            match op {
-                PatchOperation::UpdateFile { chunks, .. } => {
-                    if !chunks.is_empty() {
-                        let (new_content, lines, inserted) = apply_update_chunks(&result_content, &chunks)?;
-                        result_content = new_content;
-                        result_lines = lines;
-                        result_inserted = inserted;
+    // Apply every UpdateFile operation in order; Add/Delete File operations
+    // touch other pages, so they are kept until the expansion is saved
+    let mut updated_markdown = content.clone();
+    let mut updated_lines = Vec::new();
+    let mut inserted_parts = Vec::new();
+    let mut file_operations = Vec::new();
+    let mut approximate = false;
+
+    // Unanchored changes go right after the selection rather than at the end
+    let mut fallback_line = (selection.end_line > 0).then_some(selection.end_line);
+
+    for op in operations {
+        match op {
+            PatchOperation::UpdateFile { path, chunks } => {
+                // Only the page being expanded can be updated
+                if path != page_name && path != "content.md" {
+                    return Err(format!(
+                        "The expansion tried to update '{}', which is not the page being expanded",
+                        path
+                    ));
+                }
+                if !chunks.is_empty() {
+                    let (new_content, lines, inserted, approx) =
+                        apply_update_chunks(&updated_markdown, &chunks, &mut fallback_line)?;
+                    approximate |= approx;
+                    updated_markdown = new_content;
+                    updated_lines.extend(lines);
+                    if !inserted.is_empty() {
+                        inserted_parts.push(inserted);
                     }
                 }
-                _ => {} // Ignore Add/Delete for expansion
             }
+            op => file_operations.push(op),
         }
-
-        (result_content, result_lines, result_inserted)
-    };
+    }
+    updated_lines.sort_unstable();
+    updated_lines.dedup();
+    let inserted_content = inserted_parts.join("\n");
 
     // Generate expansion ID
     let expansion_id = format!("exp_{}", Uuid::new_v4().to_string().split('-').next().unwrap());
@@ -1163,18 +2736,203 @@ pub async fn expand_selection_with_ai(
     // Determine insertion line from updated lines
     let insertion_line = updated_lines.first().copied().unwrap_or(1);
 
-    // Save the updated content
-    save_page_content(project_id, page_name, &updated_markdown)?;
-
-    Ok(ExpansionResult {
-        expansion_id,
-        updated_markdown: updated_markdown.clone(),
-        inserted_content,
-        insertion_line,
-        updated_lines,
+    Ok(GeneratedExpansion {
+        original_content: content,
+        result: ExpansionResult {
+            expansion_id,
+            updated_markdown,
+            inserted_content,
+            insertion_line,
+            updated_lines,
+            added_pages: Vec::new(),
+            deleted_pages: Vec::new(),
+            approximate,
+        },
+        file_operations,
     })
 }
 
+/// Check that an expansion's Add/Delete File operations can all be carried
+/// out, before anything is written
+fn validate_expansion_file_operations(
+    project_id: &str,
+    page_name: &str,
+    operations: &[PatchOperation],
+) -> Result<(), String> {
+    let project = load_project(project_id)?;
+    for op in operations {
+        match op {
+            PatchOperation::AddFile { path, content } => {
+                if content.trim().is_empty() {
+                    return Err(format!("The expansion tried to add '{}' with no content", path));
+                }
+            }
+            PatchOperation::DeleteFile { path } => {
+                if path == page_name || path == "content.md" {
+                    return Err("An expansion can't delete the page it is expanding".to_string());
+                }
+                if !project.page_order.contains(path) {
+                    return Err(format!("The expansion tried to delete '{}', which is not in this project", path));
+                }
+            }
+            PatchOperation::UpdateFile { .. } => {}
+        }
+    }
+    Ok(())
+}
+
+/// Carry out an expansion's Add/Delete File operations, recording the
+/// affected pages on the result
+fn apply_expansion_file_operations(
+    project_id: &str,
+    operations: Vec<PatchOperation>,
+    result: &mut ExpansionResult,
+) -> Result<(), String> {
+    for op in operations {
+        match op {
+            PatchOperation::AddFile { path, content } => {
+                let title = crate::services::file_service::extract_page_title(&content)
+                    .unwrap_or_else(|| path.trim_end_matches(".md").to_string());
+                result.added_pages.push(add_page_to_project(project_id, &title, &content)?);
+            }
+            PatchOperation::DeleteFile { path } => {
+                crate::services::file_service::delete_page(project_id, &path)?;
+                result.deleted_pages.push(path);
+            }
+            PatchOperation::UpdateFile { .. } => {}
+        }
+    }
+    Ok(())
+}
+
+/// Expand a selection with AI-generated content using Codex-style patches
+/// and save it to the page right away
+pub async fn expand_selection_with_ai(
+    project_id: &str,
+    page_name: &str,
+    selection: &SelectionRange,
+    question: &str,
+    _api_key: &str,
+) -> Result<ExpansionResult, String> {
+    let expansion = generate_expansion(project_id, page_name, selection, question).await?;
+    validate_expansion_file_operations(project_id, page_name, &expansion.file_operations)?;
+
+    // Add/Delete other pages first, so a failure there leaves this page
+    // and its expansion history untouched
+    let mut result = expansion.result;
+    apply_expansion_file_operations(project_id, expansion.file_operations, &mut result)?;
+
+    // Save the updated content
+    save_page_content(project_id, page_name, &result.updated_markdown)?;
+    crate::services::file_service::record_expansion(
+        project_id,
+        page_name,
+        &result.expansion_id,
+        &expansion.original_content,
+        &result.updated_markdown,
+    )?;
+
+    Ok(result)
+}
+
+/// An expansion generated for review that hasn't been written to the page yet
+struct PendingExpansion {
+    project_id: String,
+    page_name: String,
+    original_content: String,
+    result: ExpansionResult,
+    file_operations: Vec<PatchOperation>,
+    created_at: Instant,
+}
+
+/// Previews nobody accepted or rejected within this long are dropped
+const PENDING_EXPANSION_TTL: Duration = Duration::from_secs(60 * 60);
+/// Most previews kept at once; the oldest go first
+const MAX_PENDING_EXPANSIONS: usize = 20;
+
+fn pending_expansions() -> &'static Mutex<HashMap<String, PendingExpansion>> {
+    static PENDING: OnceLock<Mutex<HashMap<String, PendingExpansion>>> = OnceLock::new();
+    PENDING.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/// Generate an expansion without saving it, so the user can review the
+/// inserted lines before calling `accept_expansion` or `reject_expansion`
+pub async fn preview_expansion(
+    project_id: &str,
+    page_name: &str,
+    selection: &SelectionRange,
+    question: &str,
+) -> Result<ExpansionResult, String> {
+    let expansion = generate_expansion(project_id, page_name, selection, question).await?;
+    let result = expansion.result.clone();
+
+    let mut pending = pending_expansions()
+        .lock()
+        .map_err(|_| "Expansion queue is poisoned".to_string())?;
+
+    // Previews the UI abandoned would otherwise hold their page until exit
+    pending.retain(|_, p| p.created_at.elapsed() < PENDING_EXPANSION_TTL);
+    while pending.len() >= MAX_PENDING_EXPANSIONS {
+        let Some(oldest) = pending.iter()
+            .min_by_key(|(_, p)| p.created_at)
+            .map(|(id, _)| id.clone())
+        else {
+            break;
+        };
+        pending.remove(&oldest);
+    }
+
+    pending.insert(result.expansion_id.clone(), PendingExpansion {
+        project_id: project_id.to_string(),
+        page_name: page_name.to_string(),
+        original_content: expansion.original_content,
+        result: expansion.result,
+        file_operations: expansion.file_operations,
+        created_at: Instant::now(),
+    });
+
+    Ok(result)
+}
+
+/// Save a previewed expansion to its page
+pub fn accept_expansion(expansion_id: &str) -> Result<ExpansionResult, String> {
+    let pending = pending_expansions()
+        .lock()
+        .map_err(|_| "Expansion queue is poisoned".to_string())?
+        .remove(expansion_id)
+        .ok_or_else(|| format!("No pending expansion '{}'", expansion_id))?;
+
+    // Don't overwrite edits made to the page after the preview was generated
+    let current = load_page_content(&pending.project_id, &pending.page_name)?;
+    if current != pending.original_content {
+        return Err("The page changed since this expansion was previewed. Please generate it again.".to_string());
+    }
+
+    validate_expansion_file_operations(&pending.project_id, &pending.page_name, &pending.file_operations)?;
+
+    let mut result = pending.result;
+    apply_expansion_file_operations(&pending.project_id, pending.file_operations, &mut result)?;
+    save_page_content(&pending.project_id, &pending.page_name, &result.updated_markdown)?;
+    crate::services::file_service::record_expansion(
+        &pending.project_id,
+        &pending.page_name,
+        &result.expansion_id,
+        &pending.original_content,
+        &result.updated_markdown,
+    )?;
+
+    Ok(result)
+}
+
+/// Discard a previewed expansion without touching the page
+pub fn reject_expansion(expansion_id: &str) -> Result<(), String> {
+    pending_expansions()
+        .lock()
+        .map_err(|_| "Expansion queue is poisoned".to_string())?
+        .remove(expansion_id);
+    Ok(())
+}
+
 // ============================================================================
 // ANSWER-ONLY MODE (no document modification)
 // ============================================================================
@@ -1192,27 +2950,49 @@ Your task is to provide a clear, concise answer to their question.
 5. NO emojis
 6. Do not reference the document or say things like "as mentioned" - just answer directly"#;
 
-/// Answer a question about selected text without modifying the document
+/// Answer a question about selected text without modifying the document.
+/// When a page is given, its content is included so questions about "this"
+/// or "the above" can be answered from the surrounding text.
 pub async fn answer_question(
     selection: &SelectionRange,
     question: &str,
+    page: Option<(&str, &str)>,
     _api_key: &str,
 ) -> Result<String, String> {
     // Create LLM client from config
-    let client = LlmClient::from_config()?;
+    let client = LlmClient::from_config(page.map(|(project_id, _)| project_id))?;
 
     // Build the prompt
-    let user_prompt = format!(
+    let mut user_prompt = format!(
         "Selected text: \"{}\"\n\nQuestion: {}",
         selection.selected_text, question
     );
 
+    if let Some((project_id, page_name)) = page {
+        let content = load_page_content(project_id, page_name)?;
+
+        // Same context budget as expansions; fall back to the selection alone
+        // if even the area around it doesn't fit
+        let context_limit = config_service::get_context_limit()?;
+        let overhead = estimate_tokens(ANSWER_SYSTEM_PROMPT) + estimate_tokens(&user_prompt) + 50;
+        let budget = context_limit.saturating_sub(EXPANSION_RESPONSE_RESERVE_TOKENS + overhead);
+        let document_context = if estimate_tokens(&content) <= budget {
+            Some(content)
+        } else {
+            truncate_around_selection(&content, selection, budget * 4)
+        };
+
+        if let Some(document_context) = document_context {
+            user_prompt = format!("## Current Document\n```\n{}\n```\n\n{}", document_context, user_prompt);
+        }
+    }
+
     let messages = vec![
         LlmClient::system_message(ANSWER_SYSTEM_PROMPT),
         LlmClient::user_message(&user_prompt),
     ];
 
-    let response = client.chat_completion(messages, Some(0.7)).await?;
+    let response = client.chat_completion(messages, Some(config_service::get_temperature())).await?;
 
     Ok(response.trim().to_string())
 }
@@ -1296,7 +3076,19 @@ Deletes a page from the project.
 }
 ```
 
-### 7. respond
+### 7. move_file
+Moves a page to a new position in the book. `new_index` is zero-based (0 = first page); an index past the end moves the page to the end.
+```json
+{
+  "tool": "move_file",
+  "arguments": {
+    "filename": "05-glossary.md",
+    "new_index": 7
+  }
+}
+```
+
+### 8. respond
 Use this when you want to respond to the user without making changes, or to ask clarifying questions.
 ```json
 {
@@ -1339,6 +3131,7 @@ Your reasoning about what to do...
 - "What chapters do we have?" → list_files
 - "Improve the introduction" → read_file first, then edit_file with improvements
 - "Delete the last chapter" → list_files to confirm, then delete_file
+- "Move the glossary to the end" → list_files to find it, then move_file
 
 IMPORTANT: Always respond with exactly one tool call. Use 'respond' tool when you need to communicate with the user."##;
 
@@ -1360,60 +3153,190 @@ pub struct ChatAgentEvent {
     pub tool_name: Option<String>,
 }
 
-/// Execute delete_file tool
-fn execute_delete_file(tool_call: &ToolCall, state: &mut EditingAgentState) -> ToolResult {
-    let filename = tool_call.arguments.get("filename")
+/// Maximum characters of tool output included in a tool result event
+const TOOL_RESULT_EVENT_MAX_CHARS: usize = 500;
+
+/// Event payload emitted after the chat agent executes a tool
+#[derive(Debug, Clone, Serialize)]
+pub struct ChatToolResultEvent {
+    pub session_id: String,
+    pub tool_name: String,
+    pub success: bool,
+    pub output: String,
+    pub iteration: u32,
+}
+
+/// Truncate tool output for display, keeping to char boundaries
+fn truncate_tool_output(output: &str) -> String {
+    let trimmed = output.trim();
+    match trimmed.char_indices().nth(TOOL_RESULT_EVENT_MAX_CHARS) {
+        Some((idx, _)) => format!("{}...", &trimmed[..idx]),
+        None => trimmed.to_string(),
+    }
+}
+
+/// Longest argument value copied verbatim into a tool action summary
+const TOOL_ACTION_ARG_MAX_CHARS: usize = 80;
+
+/// Summarize tool arguments for the audit log, eliding long values such as page content
+fn summarize_tool_arguments(arguments: &serde_json::Value) -> String {
+    let Some(map) = arguments.as_object() else {
+        return String::new();
+    };
+
+    map.iter()
+        .map(|(key, value)| {
+            let text = match value {
+                serde_json::Value::String(s) => s.clone(),
+                other => other.to_string(),
+            };
+            if text.chars().count() > TOOL_ACTION_ARG_MAX_CHARS {
+                format!("{}=<{} chars>", key, text.chars().count())
+            } else {
+                format!("{}={}", key, text)
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Start an audit log entry for a tool call, capturing whatever is needed to
+/// revert it before the tool runs
+fn begin_tool_action(project_id: &str, tool_call: &ToolCall) -> ToolAction {
+    let page_name = tool_call.arguments.get("filename")
         .and_then(|v| v.as_str())
-        .unwrap_or("");
+        .map(|s| s.to_string());
 
-    // Load project and remove from page order
-    match load_project(&state.project_id) {
-        Ok(mut project) => {
-            if !project.page_order.contains(&filename.to_string()) {
-                return ToolResult {
-                    tool_name: "delete_file".to_string(),
-                    success: false,
-                    output: format!("File '{}' not found in project", filename),
-                };
+    let previous_content = match (tool_call.name.as_str(), &page_name) {
+        ("edit_file" | "delete_file", Some(name)) => load_page_content(project_id, name).ok(),
+        _ => None,
+    };
+    let previous_index = match (tool_call.name.as_str(), &page_name) {
+        ("move_file" | "delete_file", Some(name)) => load_project(project_id)
+            .ok()
+            .and_then(|p| p.page_order.iter().position(|f| f == name)),
+        _ => None,
+    };
+
+    ToolAction {
+        tool_name: tool_call.name.clone(),
+        arguments: summarize_tool_arguments(&tool_call.arguments),
+        success: false,
+        timestamp: Utc::now(),
+        page_name,
+        previous_content,
+        previous_index,
+        reverted: false,
+    }
+}
+
+/// Move a page back to `index` in the page order
+fn move_page_to(project_id: &str, page_name: &str, index: usize) -> Result<(), String> {
+    let mut project = load_project(project_id)?;
+    let from = project.page_order.iter()
+        .position(|p| p == page_name)
+        .ok_or_else(|| format!("Page '{}' not found in project", page_name))?;
+    let page = project.page_order.remove(from);
+    project.page_order.insert(index.min(project.page_order.len()), page);
+    project.updated_at = Utc::now();
+    crate::services::file_service::save_project(&project)
+}
+
+/// Undo a single recorded action. Returns a description of what was done and,
+/// for a recreated page, its new filename.
+fn revert_tool_action(
+    project_id: &str,
+    action: &ToolAction,
+    page_name: &str,
+) -> Result<(String, Option<String>), String> {
+    let exists = load_project(project_id)?.page_order.iter().any(|p| p == page_name);
+
+    match action.tool_name.as_str() {
+        "create_file" => {
+            if !exists {
+                return Err("page no longer exists".to_string());
+            }
+            crate::services::file_service::delete_page(project_id, page_name)?;
+            Ok((format!("Deleted '{}'", page_name), None))
+        }
+        "edit_file" => {
+            let content = action.previous_content.as_deref()
+                .ok_or_else(|| "previous content was not recorded".to_string())?;
+            if !exists {
+                return Err("page no longer exists".to_string());
             }
+            save_page_content(project_id, page_name, content)?;
+            Ok((format!("Restored '{}'", page_name), None))
+        }
+        "delete_file" => {
+            let content = action.previous_content.as_deref()
+                .ok_or_else(|| "previous content was not recorded".to_string())?;
+            let title = crate::services::file_service::imported_page_title(
+                std::path::Path::new(page_name),
+                content,
+            );
+            let index = action.previous_index.unwrap_or(usize::MAX);
+            let new_name = crate::services::file_service::insert_page_at(project_id, &title, content, index)?;
+            Ok((format!("Recreated '{}' as {}", page_name, new_name), Some(new_name)))
+        }
+        "move_file" => {
+            let index = action.previous_index
+                .ok_or_else(|| "previous position was not recorded".to_string())?;
+            move_page_to(project_id, page_name, index)?;
+            Ok((format!("Moved '{}' back to position {}", page_name, index), None))
+        }
+        _ => Err("action cannot be reverted".to_string()),
+    }
+}
 
-            // Remove from page order
-            project.page_order.retain(|p| p != filename);
-            project.updated_at = Utc::now();
+/// Undo the page changes a chat session's editing agent made, newest first.
+/// Actions that can no longer be undone are reported as skipped.
+pub fn revert_chat_changes(project_id: &str, session_id: &str) -> Result<ChatRevertSummary, String> {
+    let mut session = load_chat_session(project_id, session_id)?;
+    let mut summary = ChatRevertSummary::default();
+    // Pages recreated under a new filename while reverting a delete
+    let mut renamed: HashMap<String, String> = HashMap::new();
 
-            // Delete the actual file
-            let pages_dir = match crate::services::file_service::get_project_dir(&state.project_id) {
-                Ok(dir) => dir.join("pages"),
-                Err(e) => return ToolResult {
-                    tool_name: "delete_file".to_string(),
-                    success: false,
-                    output: format!("Failed to get project directory: {}", e),
-                },
-            };
+    for action in session.tool_actions.iter_mut().rev() {
+        if action.reverted || !action.success {
+            continue;
+        }
+        if !matches!(action.tool_name.as_str(), "create_file" | "edit_file" | "delete_file" | "move_file") {
+            continue;
+        }
+        let Some(recorded_name) = action.page_name.clone() else {
+            summary.skipped.push(format!("{}: no page was recorded", action.tool_name));
+            continue;
+        };
+        let page_name = renamed.get(&recorded_name).cloned().unwrap_or_else(|| recorded_name.clone());
 
-            let file_path = pages_dir.join(filename);
-            if file_path.exists() {
-                if let Err(e) = std::fs::remove_file(&file_path) {
-                    return ToolResult {
-                        tool_name: "delete_file".to_string(),
-                        success: false,
-                        output: format!("Failed to delete file: {}", e),
-                    };
+        match revert_tool_action(project_id, action, &page_name) {
+            Ok((description, new_name)) => {
+                if let Some(new_name) = new_name {
+                    renamed.insert(recorded_name, new_name);
                 }
+                action.reverted = true;
+                summary.reverted.push(description);
             }
+            Err(e) => summary.skipped.push(format!("{} '{}': {}", action.tool_name, page_name, e)),
+        }
+    }
 
-            // Save updated project
-            if let Err(e) = crate::services::file_service::save_project(&project) {
-                return ToolResult {
-                    tool_name: "delete_file".to_string(),
-                    success: false,
-                    output: format!("Failed to update project: {}", e),
-                };
-            }
+    session.updated_at = Utc::now();
+    save_chat_session(&session)?;
 
-            // Update state
-            state.pages.retain(|p| p.filename != filename);
+    Ok(summary)
+}
+
+/// Execute delete_file tool
+fn execute_delete_file(tool_call: &ToolCall, state: &mut EditingAgentState) -> ToolResult {
+    let filename = tool_call.arguments.get("filename")
+        .and_then(|v| v.as_str())
+        .unwrap_or("");
 
+    match crate::services::file_service::delete_page(&state.project_id, filename) {
+        Ok(_) => {
+            state.pages.retain(|p| p.filename != filename);
             ToolResult {
                 tool_name: "delete_file".to_string(),
                 success: true,
@@ -1423,7 +3346,53 @@ fn execute_delete_file(tool_call: &ToolCall, state: &mut EditingAgentState) -> T
         Err(e) => ToolResult {
             tool_name: "delete_file".to_string(),
             success: false,
-            output: format!("Failed to load project: {}", e),
+            output: e,
+        },
+    }
+}
+
+/// Execute move_file tool
+fn execute_move_file(tool_call: &ToolCall, state: &mut EditingAgentState) -> ToolResult {
+    let filename = tool_call.arguments.get("filename")
+        .and_then(|v| v.as_str())
+        .unwrap_or("");
+    let Some(new_index) = tool_call.arguments.get("new_index").and_then(|v| v.as_u64()) else {
+        return ToolResult {
+            tool_name: "move_file".to_string(),
+            success: false,
+            output: "new_index must be a non-negative integer".to_string(),
+        };
+    };
+
+    let result = load_project(&state.project_id).and_then(|mut project| {
+        let from = project.page_order.iter()
+            .position(|p| p == filename)
+            .ok_or_else(|| format!("Page '{}' not found in project", filename))?;
+        let page = project.page_order.remove(from);
+        let to = (new_index as usize).min(project.page_order.len());
+        project.page_order.insert(to, page);
+        project.updated_at = Utc::now();
+        crate::services::file_service::save_project(&project)?;
+        Ok(to)
+    });
+
+    match result {
+        Ok(to) => {
+            if let Some(from) = state.pages.iter().position(|p| p.filename == filename) {
+                let page = state.pages.remove(from);
+                let to = to.min(state.pages.len());
+                state.pages.insert(to, page);
+            }
+            ToolResult {
+                tool_name: "move_file".to_string(),
+                success: true,
+                output: format!("Moved '{}' to position {}", filename, to),
+            }
+        }
+        Err(e) => ToolResult {
+            tool_name: "move_file".to_string(),
+            success: false,
+            output: e,
         },
     }
 }
@@ -1475,51 +3444,7 @@ fn execute_editing_tool(tool_call: &ToolCall, state: &mut EditingAgentState) ->
                 },
             }
         }
-        "edit_file" => {
-            let filename = tool_call.arguments.get("filename")
-                .and_then(|v| v.as_str())
-                .unwrap_or("");
-
-            let old_content = tool_call.arguments.get("old_content")
-                .and_then(|v| v.as_str())
-                .unwrap_or("");
-
-            let new_content = tool_call.arguments.get("new_content")
-                .and_then(|v| v.as_str())
-                .unwrap_or("");
-
-            let current_content = match load_page_content(&state.project_id, filename) {
-                Ok(c) => c,
-                Err(e) => return ToolResult {
-                    tool_name: "edit_file".to_string(),
-                    success: false,
-                    output: format!("Failed to read file '{}': {}", filename, e),
-                },
-            };
-
-            if !current_content.contains(old_content) {
-                return ToolResult {
-                    tool_name: "edit_file".to_string(),
-                    success: false,
-                    output: format!("Could not find the specified text in '{}'. Make sure old_content matches exactly.", filename),
-                };
-            }
-
-            let updated_content = current_content.replacen(old_content, new_content, 1);
-
-            match save_page_content(&state.project_id, filename, &updated_content) {
-                Ok(()) => ToolResult {
-                    tool_name: "edit_file".to_string(),
-                    success: true,
-                    output: format!("Successfully edited '{}'", filename),
-                },
-                Err(e) => ToolResult {
-                    tool_name: "edit_file".to_string(),
-                    success: false,
-                    output: format!("Failed to save edits to '{}': {}", filename, e),
-                },
-            }
-        }
+        "edit_file" => edit_page_file(&state.project_id, tool_call),
         "read_file" => {
             let filename = tool_call.arguments.get("filename")
                 .and_then(|v| v.as_str())
@@ -1605,6 +3530,7 @@ fn execute_editing_tool(tool_call: &ToolCall, state: &mut EditingAgentState) ->
             }
         }
         "delete_file" => execute_delete_file(tool_call, state),
+        "move_file" => execute_move_file(tool_call, state),
         "respond" => execute_respond(tool_call, state),
         _ => ToolResult {
             tool_name: tool_call.name.clone(),
@@ -1621,6 +3547,7 @@ pub struct ChatAgentResult {
     pub response: String,
     pub tool_used: Option<String>,
     pub pages_changed: bool,
+    pub interrupted: bool,
 }
 
 /// Run the editing agent for a chat message
@@ -1632,16 +3559,23 @@ pub async fn run_editing_agent(
     app: AppHandle,
 ) -> Result<ChatAgentResult, String> {
     // Create LLM client from config
-    let client = LlmClient::from_config()?;
+    let client = LlmClient::from_config(Some(project_id))?.with_stop(tool_call_stop());
+
+    // Register for cancellation so the user can stop the agent mid-loop
+    let cancel = CancelRegistration::new(chat_cancel_flags(), session_id);
 
     // Load the session to get history
     let mut session = load_chat_session(project_id, session_id)?;
 
+    // Flush queued messages and tool actions however the run ends
+    let _flush = ChatSessionFlush::new(project_id, session_id);
+
     // Add user message to session
     session.messages.push(ChatMessage {
         role: "user".to_string(),
         content: user_message.to_string(),
         timestamp: Utc::now(),
+        interrupted: false,
     });
 
     // Update title from first user message if still "New Chat"
@@ -1654,6 +3588,9 @@ pub async fn run_editing_agent(
         };
     }
 
+    // Persist the user message (batched if configured)
+    queue_chat_session_save(&session)?;
+
     // Load project info
     let project = load_project(project_id)?;
 
@@ -1667,23 +3604,18 @@ pub async fn run_editing_agent(
             })
             .collect(),
         iteration: 0,
-        max_iterations: 10, // Limit iterations for chat
+        max_iterations: config_service::get_max_chat_iterations(), // Limit iterations for chat
         response_to_user: None,
     };
 
     // Build messages for LLM from session history
     let mut messages = vec![
-        LlmClient::system_message(EDITING_AGENT_SYSTEM_PROMPT),
+        LlmClient::system_message(&project_system_prompt(EDITING_AGENT_SYSTEM_PROMPT, &project)),
     ];
 
-    // Add conversation history (last 20 messages to avoid token limits)
-    let history_start = if session.messages.len() > 20 {
-        session.messages.len() - 20
-    } else {
-        0
-    };
-
-    for msg in &session.messages[history_start..] {
+    // Add conversation history; trim_agent_messages keeps it within the
+    // context window
+    for msg in &session.messages {
         if msg.role == "user" {
             messages.push(LlmClient::user_message(&msg.content));
         } else {
@@ -1691,9 +3623,12 @@ pub async fn run_editing_agent(
         }
     }
 
+    let context_budget = agent_context_budget();
     let mut final_response = String::new();
     let mut tool_used: Option<String> = None;
     let mut pages_changed = false;
+    let mut interrupted = false;
+    let mut completed_actions: Vec<String> = Vec::new();
 
     // Emit starting status
     let _ = app.emit("chat-agent-status", ChatAgentEvent {
@@ -1705,10 +3640,27 @@ pub async fn run_editing_agent(
 
     // Agent loop - continue until we get a response to user or hit limit
     while state.iteration < state.max_iterations && state.response_to_user.is_none() {
+        if cancel.is_cancelled() {
+            interrupted = true;
+            break;
+        }
+
         state.iteration += 1;
 
         // Call the LLM
-        let response = client.chat_completion(messages.clone(), Some(0.7)).await?;
+        trim_agent_messages(&mut messages, context_budget);
+        let completion = client
+            .chat_completion_full(messages.clone(), Some(config_service::get_temperature()))
+            .await?;
+        if let Some(thinking) = completion.reasoning.as_deref().and_then(summarize_reasoning) {
+            let _ = app.emit("chat-agent-status", ChatAgentEvent {
+                session_id: session_id.to_string(),
+                status: "thinking".to_string(),
+                message: Some(thinking),
+                tool_name: None,
+            });
+        }
+        let response = close_tool_call(completion.content);
 
         // Add assistant response to messages
         messages.push(LlmClient::assistant_message(&response));
@@ -1732,21 +3684,43 @@ pub async fn run_editing_agent(
         });
 
         // Track if pages might have changed
-        if matches!(tool_call.name.as_str(), "create_file" | "edit_file" | "delete_file") {
+        if matches!(tool_call.name.as_str(), "create_file" | "edit_file" | "delete_file" | "move_file") {
             pages_changed = true;
         }
 
         tool_used = Some(tool_call.name.clone());
 
-        // Execute the tool
+        // Execute the tool, capturing what is needed to revert it first
+        let mut action = begin_tool_action(project_id, &tool_call);
         let result = execute_editing_tool(&tool_call, &mut state);
 
+        // Emit the tool result so the UI can show what the agent did
+        let _ = app.emit("chat-agent-tool-result", ChatToolResultEvent {
+            session_id: session_id.to_string(),
+            tool_name: result.tool_name.clone(),
+            success: result.success,
+            output: truncate_tool_output(&result.output),
+            iteration: state.iteration,
+        });
+
         // If it was a respond tool, we're done
         if tool_call.name == "respond" {
             final_response = result.output.clone();
             break;
         }
 
+        // Record the action in the session's audit log
+        action.success = result.success;
+        if tool_call.name == "create_file" && result.success {
+            action.page_name = state.pages.last().map(|p| p.filename.clone());
+        }
+        session.tool_actions.push(action);
+        queue_chat_session_save(&session)?;
+
+        if result.success {
+            completed_actions.push(result.output.lines().next().unwrap_or("").to_string());
+        }
+
         // Add tool result to messages for next iteration
         let result_msg = if result.success {
             format!("Tool '{}' executed successfully:\n{}", result.tool_name, result.output)
@@ -1761,13 +3735,25 @@ pub async fn run_editing_agent(
         final_response = resp;
     }
 
+    // If stopped by the user, report what was done so far instead of asking for a summary
+    if interrupted && final_response.is_empty() {
+        final_response = if completed_actions.is_empty() {
+            "Stopped before making any changes.".to_string()
+        } else {
+            format!(
+                "Stopped before finishing. Changes made so far:\n{}",
+                completed_actions.iter().map(|a| format!("- {}", a)).collect::<Vec<_>>().join("\n")
+            )
+        };
+    }
+
     // If still no response, ask agent to summarize what it did
     if final_response.is_empty() {
         messages.push(LlmClient::user_message(
             "Now use the respond tool to tell the user what you did."
         ));
 
-        if let Ok(summary_response) = client.chat_completion(messages, Some(0.7)).await {
+        if let Ok(summary_response) = client.chat_completion(messages, Some(config_service::get_temperature())).await {
             if let Ok(tool_call) = parse_tool_call(&summary_response) {
                 if tool_call.name == "respond" {
                     if let Some(msg) = tool_call.arguments.get("message").and_then(|v| v.as_str()) {
@@ -1788,16 +3774,17 @@ pub async fn run_editing_agent(
         role: "assistant".to_string(),
         content: final_response.clone(),
         timestamp: Utc::now(),
+        interrupted,
     });
     session.updated_at = Utc::now();
 
-    // Save session
+    // Save session (also flushes any batched messages before we return)
     save_chat_session(&session)?;
 
     // Emit completion status
     let _ = app.emit("chat-agent-status", ChatAgentEvent {
         session_id: session_id.to_string(),
-        status: "complete".to_string(),
+        status: if interrupted { "interrupted" } else { "complete" }.to_string(),
         message: None,
         tool_name: None,
     });
@@ -1806,5 +3793,85 @@ pub async fn run_editing_agent(
         response: final_response,
         tool_used,
         pages_changed,
+        interrupted,
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::file_service::TestProject;
+
+    #[test]
+    fn find_tool_json_handles_deeply_nested_arguments() {
+        let response = r#"Sure, creating it now.
+{"tool": "create_file", "arguments": {"title": "Graphs", "meta": {"outline": {"sections": [{"name": "BFS"}, {"name": "DFS"}]}}}}
+Done."#;
+        let json = find_tool_json(response).unwrap();
+        assert!(json.starts_with(r#"{"tool""#));
+        assert!(json.ends_with("}}}}"));
+
+        let tool_call = parse_tool_call(response).unwrap();
+        assert_eq!(tool_call.name, "create_file");
+        assert_eq!(tool_call.arguments["meta"]["outline"]["sections"][1]["name"], "DFS");
+    }
+
+    #[test]
+    fn find_tool_json_ignores_braces_inside_strings() {
+        let response = r#"{"tool": "edit_file", "arguments": {"old_content": "fn main() {", "new_content": "a \"}\" b }}"}}"#;
+        assert_eq!(find_tool_json(response), Some(response));
+
+        let tool_call = parse_tool_call(response).unwrap();
+        assert_eq!(tool_call.arguments["new_content"], r#"a "}" b }}"#);
+    }
+
+    #[test]
+    fn balanced_object_len_stops_at_the_matching_brace() {
+        assert_eq!(balanced_object_len(r#"{"a": {"b": "}"}} trailing"#), Some(17));
+        assert_eq!(balanced_object_len(r#"{"a": {"b": 1}"#), None);
+    }
+
+    /// A throwaway project holding one page, `01-page.md`
+    fn test_page(content: &str) -> TestProject {
+        let project = TestProject::new();
+        save_page_content(&project.id, "01-page.md", content).unwrap();
+        project
+    }
+
+    fn edit_call(old_content: &str, new_content: &str) -> ToolCall {
+        ToolCall {
+            name: "edit_file".to_string(),
+            arguments: serde_json::json!({
+                "filename": "01-page.md",
+                "old_content": old_content,
+                "new_content": new_content,
+            }),
+        }
+    }
+
+    #[test]
+    fn edit_page_file_reports_no_effective_change() {
+        let project = test_page("# Title\n\nSome text.\n");
+        let result = edit_page_file(&project.id, &edit_call("Some text.", "Some text."));
+        assert!(!result.success);
+        assert!(result.output.contains("No effective change"));
+        assert_eq!(load_page_content(&project.id, "01-page.md").unwrap(), "# Title\n\nSome text.\n");
+    }
+
+    #[test]
+    fn edit_page_file_rejects_ambiguous_matches() {
+        let content = "# Title\n\nSee below.\n\nSee below.\n";
+        let project = test_page(content);
+        let result = edit_page_file(&project.id, &edit_call("See below.", "See above."));
+        assert!(!result.success);
+        assert!(result.output.contains("matches 2 places"));
+        assert_eq!(load_page_content(&project.id, "01-page.md").unwrap(), content);
+
+        let result = edit_page_file(&project.id, &edit_call("# Title\n\nSee below.", "# Title\n\nSee above."));
+        assert!(result.success);
+        assert_eq!(
+            load_page_content(&project.id, "01-page.md").unwrap(),
+            "# Title\n\nSee above.\n\nSee below.\n"
+        );
+    }
+}
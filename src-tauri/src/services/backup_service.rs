@@ -0,0 +1,428 @@
+use std::fs::{self, File};
+use std::io::{self, Read, Seek, Write};
+use std::path::{Path, PathBuf};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipArchive, ZipWriter};
+use uuid::Uuid;
+use crate::models::{ChatSession, ProjectMeta};
+use super::config_service::{self, Config};
+use super::file_service::{self, get_app_data_dir, get_project_dir};
+
+/// Bumped whenever the backup layout changes incompatibly
+const BACKUP_FORMAT_VERSION: u32 = 1;
+const MANIFEST_NAME: &str = "liminal-backup.json";
+const CONFIG_NAME: &str = "config.json";
+const AUTH_NAME: &str = "auth.json";
+
+/// Top-level files in the data dir that are never copied as-is.
+/// The config is written separately so the API key can be stripped.
+const EXCLUDED_FILES: &[&str] = &[AUTH_NAME, CONFIG_NAME];
+
+/// Bumped whenever the project bundle layout changes incompatibly
+const BUNDLE_FORMAT_VERSION: u32 = 1;
+const BUNDLE_MANIFEST_NAME: &str = "liminal-bundle.json";
+const PROJECT_META_NAME: &str = "meta.json";
+/// Project subdirectories carried in a bundle
+const BUNDLE_DIRS: &[&str] = &["pages", "chats"];
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BackupManifest {
+    format_version: u32,
+    app_version: String,
+    created_at: DateTime<Utc>,
+    includes_api_key: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BundleManifest {
+    format_version: u32,
+    app_version: String,
+    created_at: DateTime<Utc>,
+}
+
+/// Zip the whole app data directory into a single backup archive
+pub fn create_backup(output_path: &str, include_api_key: bool) -> Result<(), String> {
+    // Make sure chat messages still waiting in the save queue are included
+    file_service::flush_all_chat_sessions()?;
+
+    let data_dir = get_app_data_dir()?;
+    let output = Path::new(output_path);
+
+    let file = File::create(output)
+        .map_err(|e| format!("Failed to create backup file: {}", e))?;
+
+    let result = write_backup(file, output, &data_dir, include_api_key);
+    if result.is_err() {
+        // Don't leave a truncated archive behind
+        let _ = fs::remove_file(output);
+    }
+    result
+}
+
+fn write_backup(file: File, output: &Path, data_dir: &Path, include_api_key: bool) -> Result<(), String> {
+    let mut zip = ZipWriter::new(file);
+    let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+
+    let manifest = BackupManifest {
+        format_version: BACKUP_FORMAT_VERSION,
+        app_version: env!("CARGO_PKG_VERSION").to_string(),
+        created_at: Utc::now(),
+        includes_api_key: include_api_key,
+    };
+    write_json_entry(&mut zip, MANIFEST_NAME, &manifest, options)?;
+
+    let mut config = config_service::load_config()?;
+    if !include_api_key {
+        config.api_key = None;
+    }
+    write_json_entry(&mut zip, CONFIG_NAME, &config, options)?;
+
+    // The backup may be written inside the data dir (e.g. exports/), so skip it
+    let skip = output.canonicalize().unwrap_or_else(|_| output.to_path_buf());
+    add_dir_to_zip(&mut zip, data_dir, data_dir, &skip, options)?;
+
+    zip.finish()
+        .map_err(|e| format!("Failed to finish backup: {}", e))?;
+
+    Ok(())
+}
+
+fn write_json_entry<W: Write + Seek, T: Serialize>(
+    zip: &mut ZipWriter<W>,
+    name: &str,
+    value: &T,
+    options: SimpleFileOptions,
+) -> Result<(), String> {
+    let content = serde_json::to_string_pretty(value)
+        .map_err(|e| format!("Failed to serialize {}: {}", name, e))?;
+    zip.start_file(name, options)
+        .map_err(|e| format!("Failed to add {} to backup: {}", name, e))?;
+    zip.write_all(content.as_bytes())
+        .map_err(|e| format!("Failed to add {} to backup: {}", name, e))
+}
+
+fn add_dir_to_zip<W: Write + Seek>(
+    zip: &mut ZipWriter<W>,
+    root: &Path,
+    dir: &Path,
+    skip: &Path,
+    options: SimpleFileOptions,
+) -> Result<(), String> {
+    let entries = fs::read_dir(dir)
+        .map_err(|e| format!("Failed to read {:?}: {}", dir, e))?;
+
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let file_type = entry.file_type().map_err(|e| e.to_string())?;
+
+        let relative: Vec<String> = path.strip_prefix(root)
+            .map_err(|e| e.to_string())?
+            .components()
+            .map(|c| c.as_os_str().to_string_lossy().to_string())
+            .collect();
+        let name = relative.join("/");
+
+        if relative.len() == 1 && EXCLUDED_FILES.contains(&name.as_str()) {
+            continue;
+        }
+
+        if file_type.is_dir() {
+            zip.add_directory(format!("{}/", name), options)
+                .map_err(|e| format!("Failed to add {} to backup: {}", name, e))?;
+            add_dir_to_zip(zip, root, &path, skip, options)?;
+        } else if file_type.is_file() {
+            if path.canonicalize().is_ok_and(|p| p == skip) {
+                continue;
+            }
+            let mut file = File::open(&path)
+                .map_err(|e| format!("Failed to read {}: {}", name, e))?;
+            zip.start_file(name.as_str(), options)
+                .map_err(|e| format!("Failed to add {} to backup: {}", name, e))?;
+            io::copy(&mut file, zip)
+                .map_err(|e| format!("Failed to add {} to backup: {}", name, e))?;
+        }
+        // Symlinks are skipped
+    }
+
+    Ok(())
+}
+
+/// Restore a backup created by `create_backup` into the app data directory.
+/// Files in the backup overwrite existing ones; other existing files are kept.
+pub fn restore_backup(backup_path: &str) -> Result<(), String> {
+    let file = File::open(backup_path)
+        .map_err(|e| format!("Failed to open backup: {}", e))?;
+    let mut archive = ZipArchive::new(file)
+        .map_err(|e| format!("Failed to read backup archive: {}", e))?;
+
+    let manifest: BackupManifest = {
+        let mut entry = archive.by_name(MANIFEST_NAME)
+            .map_err(|_| "This file is not a Liminal backup".to_string())?;
+        let mut content = String::new();
+        entry.read_to_string(&mut content)
+            .map_err(|e| format!("Failed to read backup manifest: {}", e))?;
+        serde_json::from_str(&content)
+            .map_err(|e| format!("Failed to parse backup manifest: {}", e))?
+    };
+
+    if manifest.format_version > BACKUP_FORMAT_VERSION {
+        return Err(format!(
+            "This backup was created by a newer version of Liminal ({}). Please update the app before restoring it.",
+            manifest.app_version
+        ));
+    }
+
+    // Validate every entry before extracting anything so a malicious
+    // archive can't leave a half-restored data dir behind
+    let mut entry_paths: Vec<PathBuf> = Vec::with_capacity(archive.len());
+    for i in 0..archive.len() {
+        let entry = archive.by_index(i)
+            .map_err(|e| format!("Failed to read backup entry: {}", e))?;
+        let path = entry.enclosed_name()
+            .ok_or_else(|| format!("Backup contains an unsafe path: {}", entry.name()))?;
+        entry_paths.push(path);
+    }
+
+    let data_dir = get_app_data_dir()?;
+
+    for (i, relative) in entry_paths.iter().enumerate() {
+        let name = relative.to_string_lossy().to_string();
+        if name == MANIFEST_NAME || name == AUTH_NAME {
+            continue;
+        }
+
+        let mut entry = archive.by_index(i)
+            .map_err(|e| format!("Failed to read backup entry: {}", e))?;
+
+        if name == CONFIG_NAME {
+            restore_config(&mut entry)?;
+            continue;
+        }
+
+        let dest = data_dir.join(relative);
+        if entry.is_dir() {
+            fs::create_dir_all(&dest).map_err(|e| e.to_string())?;
+            continue;
+        }
+
+        if let Some(parent) = dest.parent() {
+            fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+        }
+        let mut out = File::create(&dest)
+            .map_err(|e| format!("Failed to restore {}: {}", name, e))?;
+        io::copy(&mut entry, &mut out)
+            .map_err(|e| format!("Failed to restore {}: {}", name, e))?;
+    }
+
+    Ok(())
+}
+
+/// Restore settings from a backup, keeping the current API key if the
+/// backup was made without one
+fn restore_config<R: Read>(entry: &mut R) -> Result<(), String> {
+    let mut content = String::new();
+    entry.read_to_string(&mut content)
+        .map_err(|e| format!("Failed to read backed up config: {}", e))?;
+    let mut config: Config = serde_json::from_str(&content)
+        .map_err(|e| format!("Failed to parse backed up config: {}", e))?;
+
+    if config.api_key.is_none() {
+        config.api_key = config_service::load_config().unwrap_or_default().api_key;
+    }
+
+    config_service::save_config(&config)
+}
+
+// ============================================================================
+// Project Bundles
+// ============================================================================
+
+/// Zip a single project (meta.json, pages and chats) into a portable
+/// `.liminal` bundle
+pub fn export_project_bundle(project_id: &str, output_path: &str) -> Result<(), String> {
+    // Make sure chat messages still waiting in the save queue are included
+    file_service::flush_all_chat_sessions()?;
+
+    let project_dir = get_project_dir(project_id)?;
+    let meta_path = project_dir.join(PROJECT_META_NAME);
+    if !meta_path.is_file() {
+        return Err(format!("Project '{}' not found", project_id));
+    }
+
+    let output = Path::new(output_path);
+    let file = File::create(output)
+        .map_err(|e| format!("Failed to create bundle file: {}", e))?;
+
+    let result = write_bundle(file, output, &project_dir, &meta_path);
+    if result.is_err() {
+        // Don't leave a truncated bundle behind
+        let _ = fs::remove_file(output);
+    }
+    result
+}
+
+fn write_bundle(file: File, output: &Path, project_dir: &Path, meta_path: &Path) -> Result<(), String> {
+    let mut zip = ZipWriter::new(file);
+    let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+
+    let manifest = BundleManifest {
+        format_version: BUNDLE_FORMAT_VERSION,
+        app_version: env!("CARGO_PKG_VERSION").to_string(),
+        created_at: Utc::now(),
+    };
+    write_json_entry(&mut zip, BUNDLE_MANIFEST_NAME, &manifest, options)?;
+
+    let mut meta = File::open(meta_path)
+        .map_err(|e| format!("Failed to read project: {}", e))?;
+    zip.start_file(PROJECT_META_NAME, options)
+        .map_err(|e| format!("Failed to add {} to bundle: {}", PROJECT_META_NAME, e))?;
+    io::copy(&mut meta, &mut zip)
+        .map_err(|e| format!("Failed to add {} to bundle: {}", PROJECT_META_NAME, e))?;
+
+    let skip = output.canonicalize().unwrap_or_else(|_| output.to_path_buf());
+    for dir_name in BUNDLE_DIRS {
+        let dir = project_dir.join(dir_name);
+        if dir.is_dir() {
+            zip.add_directory(format!("{}/", dir_name), options)
+                .map_err(|e| format!("Failed to add {} to bundle: {}", dir_name, e))?;
+            add_dir_to_zip(&mut zip, project_dir, &dir, &skip, options)?;
+        }
+    }
+
+    zip.finish()
+        .map_err(|e| format!("Failed to finish bundle: {}", e))?;
+
+    Ok(())
+}
+
+/// Import a bundle created by `export_project_bundle` as a new project. The
+/// project gets a fresh id so importing never overwrites an existing one.
+pub fn import_project_bundle(zip_path: &str) -> Result<ProjectMeta, String> {
+    let file = File::open(zip_path)
+        .map_err(|e| format!("Failed to open bundle: {}", e))?;
+    let mut archive = ZipArchive::new(file)
+        .map_err(|e| format!("Failed to read bundle archive: {}", e))?;
+
+    let manifest: BundleManifest = serde_json::from_str(&read_entry(&mut archive, BUNDLE_MANIFEST_NAME)
+        .map_err(|_| "This file is not a Liminal project bundle".to_string())?)
+        .map_err(|e| format!("Failed to parse bundle manifest: {}", e))?;
+
+    if manifest.format_version > BUNDLE_FORMAT_VERSION {
+        return Err(format!(
+            "This bundle was created by a newer version of Liminal ({}). Please update the app before importing it.",
+            manifest.app_version
+        ));
+    }
+
+    let mut meta: ProjectMeta = serde_json::from_str(&read_entry(&mut archive, PROJECT_META_NAME)?)
+        .map_err(|e| format!("Failed to parse bundled project: {}", e))?;
+
+    // Validate every entry before extracting anything
+    let mut entry_paths: Vec<PathBuf> = Vec::with_capacity(archive.len());
+    for i in 0..archive.len() {
+        let entry = archive.by_index(i)
+            .map_err(|e| format!("Failed to read bundle entry: {}", e))?;
+        let path = entry.enclosed_name()
+            .ok_or_else(|| format!("Bundle contains an unsafe path: {}", entry.name()))?;
+        entry_paths.push(path);
+    }
+
+    // Page names are joined onto the pages directory, so they must not
+    // be able to point anywhere else
+    if let Some(page) = meta.page_order.iter().find(|p| !is_bare_file_name(p)) {
+        return Err(format!("Bundle contains an invalid page name: {}", page));
+    }
+
+    meta.id = Uuid::new_v4().to_string();
+    file_service::save_project(&meta)?;
+
+    let result = extract_bundle_files(&mut archive, &entry_paths, &meta.id)
+        .and_then(|_| check_bundled_pages(&meta));
+    if let Err(e) = result {
+        let _ = file_service::delete_project_dir(&meta.id);
+        return Err(e);
+    }
+
+    Ok(meta)
+}
+
+/// A single path component: no separators and not `.` or `..`
+fn is_bare_file_name(name: &str) -> bool {
+    !name.is_empty() && !name.contains(['/', '\\']) && name != "." && name != ".."
+}
+
+/// Every page in the imported page order must have been extracted
+fn check_bundled_pages(meta: &ProjectMeta) -> Result<(), String> {
+    let pages_dir = get_project_dir(&meta.id)?.join("pages");
+    match meta.page_order.iter().find(|p| !pages_dir.join(p).is_file()) {
+        Some(page) => Err(format!("Bundle is missing page '{}'", page)),
+        None => Ok(()),
+    }
+}
+
+fn read_entry<R: Read + Seek>(archive: &mut ZipArchive<R>, name: &str) -> Result<String, String> {
+    let mut entry = archive.by_name(name)
+        .map_err(|_| format!("Bundle is missing {}", name))?;
+    let mut content = String::new();
+    entry.read_to_string(&mut content)
+        .map_err(|e| format!("Failed to read {}: {}", name, e))?;
+    Ok(content)
+}
+
+fn extract_bundle_files<R: Read + Seek>(
+    archive: &mut ZipArchive<R>,
+    entry_paths: &[PathBuf],
+    project_id: &str,
+) -> Result<(), String> {
+    let project_dir = get_project_dir(project_id)?;
+
+    for (i, relative) in entry_paths.iter().enumerate() {
+        let top = relative.components().next()
+            .map(|c| c.as_os_str().to_string_lossy().to_string())
+            .unwrap_or_default();
+        if !BUNDLE_DIRS.contains(&top.as_str()) {
+            continue;
+        }
+
+        let mut entry = archive.by_index(i)
+            .map_err(|e| format!("Failed to read bundle entry: {}", e))?;
+        let name = relative.to_string_lossy().to_string();
+        let dest = project_dir.join(relative);
+
+        if entry.is_dir() {
+            fs::create_dir_all(&dest).map_err(|e| e.to_string())?;
+            continue;
+        }
+
+        // Chat sessions record their project id, so point them at the new one
+        if top == "chats" && relative.extension().is_some_and(|ext| ext == "json") {
+            let mut content = String::new();
+            entry.read_to_string(&mut content)
+                .map_err(|e| format!("Failed to read {}: {}", name, e))?;
+            let mut session: ChatSession = serde_json::from_str(&content)
+                .map_err(|e| format!("Failed to parse {}: {}", name, e))?;
+            session.project_id = project_id.to_string();
+            // The id becomes the file name, so don't trust one that could
+            // point outside the chats directory
+            if session.id.is_empty() || session.id.contains(['/', '\\', '.']) {
+                session.id = Uuid::new_v4().to_string();
+            }
+            file_service::save_chat_session(&session)?;
+            continue;
+        }
+
+        if let Some(parent) = dest.parent() {
+            fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+        }
+        let mut out = File::create(&dest)
+            .map_err(|e| format!("Failed to import {}: {}", name, e))?;
+        io::copy(&mut entry, &mut out)
+            .map_err(|e| format!("Failed to import {}: {}", name, e))?;
+    }
+
+    Ok(())
+}
@@ -1,13 +1,22 @@
-use std::fs;
-use std::path::PathBuf;
-use crate::models::{ProjectMeta, ProjectListItem, ChatSession, ChatSessionListItem, ChatMessage};
+use std::collections::{HashMap, HashSet};
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+use crate::models::{
+    ProjectMeta, ProjectListItem, TrashedProject, ChatSession, ChatSessionListItem, ChatMessage,
+    ProjectRevision, RevisionManifest, PageVersion, ImportedPage, PageDetails, ExpansionRecord,
+    Annotation, ProjectStats
+};
+use super::config_service;
+use serde::Serialize;
 use chrono::Utc;
+use regex::Regex;
 use uuid::Uuid;
 
 pub fn get_app_data_dir() -> Result<PathBuf, String> {
-    let data_dir = dirs::data_dir()
-        .ok_or("Could not find data directory")?
-        .join("Liminal");
+    let data_dir = app_data_root()?;
 
     if !data_dir.exists() {
         fs::create_dir_all(&data_dir).map_err(|e| e.to_string())?;
@@ -16,6 +25,42 @@ pub fn get_app_data_dir() -> Result<PathBuf, String> {
     Ok(data_dir)
 }
 
+#[cfg(not(test))]
+fn app_data_root() -> Result<PathBuf, String> {
+    Ok(dirs::data_dir()
+        .ok_or("Could not find data directory")?
+        .join("Liminal"))
+}
+
+/// Tests get a scratch data dir so they never touch the user's library
+#[cfg(test)]
+fn app_data_root() -> Result<PathBuf, String> {
+    Ok(std::env::temp_dir().join(format!("liminal-test-{}", std::process::id())))
+}
+
+/// A throwaway project for tests, deleted when dropped so a failing
+/// assertion doesn't leave it behind
+#[cfg(test)]
+pub(crate) struct TestProject {
+    pub id: String,
+}
+
+#[cfg(test)]
+impl TestProject {
+    pub fn new() -> Self {
+        Self { id: format!("test-{}", Uuid::new_v4()) }
+    }
+}
+
+#[cfg(test)]
+impl Drop for TestProject {
+    fn drop(&mut self) {
+        if let Ok(dir) = get_project_dir(&self.id) {
+            let _ = fs::remove_dir_all(dir);
+        }
+    }
+}
+
 pub fn get_projects_dir() -> Result<PathBuf, String> {
     let projects_dir = get_app_data_dir()?.join("projects");
 
@@ -26,6 +71,75 @@ pub fn get_projects_dir() -> Result<PathBuf, String> {
     Ok(projects_dir)
 }
 
+pub fn get_exports_dir() -> Result<PathBuf, String> {
+    let exports_dir = get_app_data_dir()?.join("exports");
+
+    if !exports_dir.exists() {
+        fs::create_dir_all(&exports_dir)
+            .map_err(|e| format!("Failed to create exports directory: {}", e))?;
+    }
+
+    Ok(exports_dir)
+}
+
+/// Locations of everything the app stores on disk
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StoragePaths {
+    pub app_data_dir: String,
+    pub projects_dir: String,
+    pub exports_dir: String,
+    pub config_path: String,
+    pub config_exists: bool,
+}
+
+pub fn get_storage_paths() -> Result<StoragePaths, String> {
+    let config_path = config_service::get_config_path()?;
+
+    Ok(StoragePaths {
+        app_data_dir: get_app_data_dir()?.to_string_lossy().to_string(),
+        projects_dir: get_projects_dir()?.to_string_lossy().to_string(),
+        exports_dir: get_exports_dir()?.to_string_lossy().to_string(),
+        config_exists: config_path.exists(),
+        config_path: config_path.to_string_lossy().to_string(),
+    })
+}
+
+/// The app data directory (projects, exports, config) as a display string
+pub fn get_data_dir() -> Result<String, String> {
+    Ok(get_app_data_dir()?.to_string_lossy().to_string())
+}
+
+/// Show the app data directory in the system file manager
+pub fn open_data_dir() -> Result<(), String> {
+    let data_dir = get_app_data_dir()?;
+    tauri_plugin_opener::open_path(&data_dir, None::<&str>)
+        .map_err(|e| format!("Failed to open data directory: {}", e))
+}
+
+/// Write a file by writing a `.tmp` sibling and renaming it over the target,
+/// so a crash mid-write leaves the old file intact instead of a truncated one
+pub fn write_atomic(path: impl AsRef<Path>, contents: impl AsRef<[u8]>) -> io::Result<()> {
+    let path = path.as_ref();
+    let file_name = path.file_name()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "Path has no file name"))?;
+    let mut tmp_name = file_name.to_os_string();
+    tmp_name.push(".tmp");
+    let tmp_path = path.with_file_name(tmp_name);
+
+    let result = File::create(&tmp_path)
+        .and_then(|mut file| {
+            file.write_all(contents.as_ref())?;
+            file.sync_all()
+        })
+        .and_then(|_| fs::rename(&tmp_path, path));
+
+    if result.is_err() {
+        let _ = fs::remove_file(&tmp_path);
+    }
+    result
+}
+
 pub fn get_project_dir(project_id: &str) -> Result<PathBuf, String> {
     let project_dir = get_projects_dir()?.join(project_id);
     Ok(project_dir)
@@ -37,7 +151,7 @@ pub fn list_all_projects() -> Result<Vec<ProjectListItem>, String> {
 
     if let Ok(entries) = fs::read_dir(&projects_dir) {
         for entry in entries.flatten() {
-            if entry.path().is_dir() {
+            if entry.path().is_dir() && entry.file_name() != TRASH_DIR_NAME {
                 let meta_path = entry.path().join("meta.json");
                 if meta_path.exists() {
                     if let Ok(content) = fs::read_to_string(&meta_path) {
@@ -56,6 +170,17 @@ pub fn list_all_projects() -> Result<Vec<ProjectListItem>, String> {
     Ok(projects)
 }
 
+/// Projects carrying `tag` (case-insensitive), most recently updated first
+pub fn list_projects_by_tag(tag: &str) -> Result<Vec<ProjectListItem>, String> {
+    let tag = tag.trim().to_lowercase();
+    let projects = list_all_projects()?
+        .into_iter()
+        .filter(|p| p.tags.iter().any(|t| t.to_lowercase() == tag))
+        .collect();
+
+    Ok(projects)
+}
+
 pub fn load_project(project_id: &str) -> Result<ProjectMeta, String> {
     let meta_path = get_project_dir(project_id)?.join("meta.json");
     let content = fs::read_to_string(&meta_path)
@@ -79,7 +204,7 @@ pub fn save_project(meta: &ProjectMeta) -> Result<(), String> {
     let meta_path = project_dir.join("meta.json");
     let content = serde_json::to_string_pretty(meta)
         .map_err(|e| format!("Failed to serialize project: {}", e))?;
-    fs::write(&meta_path, content)
+    write_atomic(&meta_path, content)
         .map_err(|e| format!("Failed to write project: {}", e))?;
 
     Ok(())
@@ -93,12 +218,224 @@ pub fn delete_project_dir(project_id: &str) -> Result<(), String> {
     Ok(())
 }
 
+/// Copy a project into a new one with a fresh id, " (copy)" appended to the
+/// title and new timestamps. Every page and chat session is copied and the
+/// page order is kept as is.
+pub fn duplicate_project(project_id: &str) -> Result<ProjectMeta, String> {
+    let source = load_project(project_id)?;
+    let source_dir = get_project_dir(project_id)?;
+
+    let now = Utc::now();
+    let meta = ProjectMeta {
+        id: Uuid::new_v4().to_string(),
+        title: format!("{} (copy)", source.title),
+        created_at: now,
+        updated_at: now,
+        ..source
+    };
+    save_project(&meta)?;
+
+    let result = copy_project_files(&source_dir, project_id, &meta.id);
+    if let Err(e) = result {
+        let _ = delete_project_dir(&meta.id);
+        return Err(e);
+    }
+
+    Ok(meta)
+}
+
+fn copy_project_files(source_dir: &std::path::Path, source_id: &str, new_id: &str) -> Result<(), String> {
+    let pages_dir = get_project_dir(new_id)?.join("pages");
+    if let Ok(entries) = fs::read_dir(source_dir.join("pages")) {
+        for entry in entries.flatten() {
+            if entry.path().is_file() {
+                fs::copy(entry.path(), pages_dir.join(entry.file_name()))
+                    .map_err(|e| format!("Failed to copy page: {}", e))?;
+            }
+        }
+    }
+
+    // Go through load_chat_session so messages still waiting in the save
+    // queue are copied too
+    if let Ok(entries) = fs::read_dir(source_dir.join("chats")) {
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.extension().is_some_and(|ext| ext == "json") {
+                let Some(session_id) = path.file_stem().map(|s| s.to_string_lossy().to_string()) else {
+                    continue;
+                };
+                let mut session = load_chat_session(source_id, &session_id)?;
+                session.project_id = new_id.to_string();
+                save_chat_session(&session)?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+// ============================================================================
+// Trash
+// ============================================================================
+
+const TRASH_DIR_NAME: &str = ".trash";
+
+/// Separates the project id from the deletion time in trash folder names
+const TRASH_TIMESTAMP_SEPARATOR: &str = "@";
+const TRASH_TIMESTAMP_FORMAT: &str = "%Y%m%dT%H%M%SZ";
+
+fn get_trash_dir() -> Result<PathBuf, String> {
+    let trash_dir = get_projects_dir()?.join(TRASH_DIR_NAME);
+
+    if !trash_dir.exists() {
+        fs::create_dir_all(&trash_dir).map_err(|e| e.to_string())?;
+    }
+
+    Ok(trash_dir)
+}
+
+/// Move a project into the trash instead of deleting it
+pub fn trash_project(project_id: &str) -> Result<(), String> {
+    let project_dir = get_project_dir(project_id)?;
+    if !project_dir.exists() {
+        return Ok(());
+    }
+
+    // Queued chat saves would otherwise recreate the project folder later
+    flush_all_chat_sessions()?;
+
+    let deleted_at = Utc::now().format(TRASH_TIMESTAMP_FORMAT);
+    let trashed_name = format!("{}{}{}", project_id, TRASH_TIMESTAMP_SEPARATOR, deleted_at);
+    fs::rename(&project_dir, get_trash_dir()?.join(trashed_name))
+        .map_err(|e| format!("Failed to move project to trash: {}", e))
+}
+
+/// Trash folders with the project they hold, newest deletion first
+fn trashed_project_dirs() -> Result<Vec<(PathBuf, TrashedProject)>, String> {
+    let mut trashed = Vec::new();
+
+    if let Ok(entries) = fs::read_dir(get_trash_dir()?) {
+        for entry in entries.flatten() {
+            let name = entry.file_name().to_string_lossy().to_string();
+            let Some((_, timestamp)) = name.rsplit_once(TRASH_TIMESTAMP_SEPARATOR) else {
+                continue;
+            };
+            let Ok(deleted_at) = chrono::NaiveDateTime::parse_from_str(timestamp, TRASH_TIMESTAMP_FORMAT) else {
+                continue;
+            };
+            let Ok(content) = fs::read_to_string(entry.path().join("meta.json")) else {
+                continue;
+            };
+            if let Ok(meta) = serde_json::from_str::<ProjectMeta>(&content) {
+                trashed.push((entry.path(), TrashedProject {
+                    project: meta.into(),
+                    deleted_at: deleted_at.and_utc(),
+                }));
+            }
+        }
+    }
+
+    trashed.sort_by_key(|(_, t)| std::cmp::Reverse(t.deleted_at));
+
+    Ok(trashed)
+}
+
+pub fn list_trashed_projects() -> Result<Vec<TrashedProject>, String> {
+    Ok(trashed_project_dirs()?.into_iter().map(|(_, project)| project).collect())
+}
+
+/// Move a project out of the trash. If it was trashed more than once, the
+/// most recently deleted copy is restored.
+pub fn restore_project(project_id: &str) -> Result<ProjectMeta, String> {
+    let (trashed_dir, _) = trashed_project_dirs()?
+        .into_iter()
+        .find(|(_, trashed)| trashed.project.id == project_id)
+        .ok_or_else(|| format!("Project '{}' is not in the trash", project_id))?;
+
+    let project_dir = get_project_dir(project_id)?;
+    if project_dir.exists() {
+        return Err(format!("A project with id '{}' already exists", project_id));
+    }
+
+    fs::rename(&trashed_dir, &project_dir)
+        .map_err(|e| format!("Failed to restore project: {}", e))?;
+
+    load_project(project_id)
+}
+
+/// Permanently delete everything in the trash. Returns how many projects
+/// were removed.
+pub fn empty_trash() -> Result<usize, String> {
+    let mut removed = 0;
+
+    if let Ok(entries) = fs::read_dir(get_trash_dir()?) {
+        for entry in entries.flatten() {
+            if entry.path().is_dir() {
+                fs::remove_dir_all(entry.path())
+                    .map_err(|e| format!("Failed to empty trash: {}", e))?;
+                removed += 1;
+            }
+        }
+    }
+
+    Ok(removed)
+}
+
 pub fn load_page_content(project_id: &str, page_name: &str) -> Result<String, String> {
     let page_path = get_project_dir(project_id)?.join("pages").join(page_name);
     fs::read_to_string(&page_path)
         .map_err(|e| format!("Failed to read page: {}", e))
 }
 
+/// A page's content along with its title and word count
+pub fn load_page(project_id: &str, page_name: &str) -> Result<PageDetails, String> {
+    let content = load_page_content(project_id, page_name)?;
+    let title = extract_page_title(&content)
+        .unwrap_or_else(|| page_name.trim_end_matches(".md").to_string());
+
+    Ok(PageDetails {
+        name: page_name.to_string(),
+        title,
+        word_count: content.split_whitespace().count(),
+        content,
+    })
+}
+
+/// Average adult reading speed used for reading time estimates
+const READING_WORDS_PER_MINUTE: usize = 200;
+
+/// Word, character and code block totals across a project's pages, plus
+/// its dates and chat session count
+pub fn get_project_stats(project_id: &str) -> Result<ProjectStats, String> {
+    let meta = load_project(project_id)?;
+
+    let mut total_words = 0;
+    let mut total_characters = 0;
+    let mut code_block_count = 0;
+    for page_name in &meta.page_order {
+        let content = load_page_content(project_id, page_name)?;
+        total_words += content.split_whitespace().count();
+        total_characters += content.chars().count();
+
+        // Every other fence line opens a block
+        let fences = content.lines()
+            .filter(|line| line.trim_start().starts_with("```"))
+            .count();
+        code_block_count += fences.div_ceil(2);
+    }
+
+    Ok(ProjectStats {
+        page_count: meta.page_order.len(),
+        total_words,
+        total_characters,
+        code_block_count,
+        reading_time_minutes: total_words.div_ceil(READING_WORDS_PER_MINUTE),
+        chat_session_count: list_chat_sessions(project_id)?.len(),
+        created_at: meta.created_at,
+        updated_at: meta.updated_at,
+    })
+}
+
 pub fn save_page_content(project_id: &str, page_name: &str, content: &str) -> Result<(), String> {
     let pages_dir = get_project_dir(project_id)?.join("pages");
 
@@ -107,7 +444,12 @@ pub fn save_page_content(project_id: &str, page_name: &str, content: &str) -> Re
     }
 
     let page_path = pages_dir.join(page_name);
-    fs::write(&page_path, content)
+    if let Ok(previous) = fs::read_to_string(&page_path) {
+        if previous != content {
+            record_page_version(project_id, page_name, &previous)?;
+        }
+    }
+    write_atomic(&page_path, content)
         .map_err(|e| format!("Failed to write page: {}", e))?;
 
     // Update project's updated_at
@@ -131,6 +473,15 @@ pub fn create_new_project(title: &str, description: &str) -> Result<ProjectMeta,
         created_at: now,
         updated_at: now,
         page_order: Vec::new(),
+        objectives: Vec::new(),
+        references: Vec::new(),
+        total_tokens: 0,
+        language: None,
+        custom_instructions: None,
+        tags: Vec::new(),
+        model_override: None,
+        cover_image: None,
+        target_words_per_chapter: None,
     };
 
     save_project(&meta)?;
@@ -138,189 +489,1097 @@ pub fn create_new_project(title: &str, description: &str) -> Result<ProjectMeta,
 }
 
 pub fn add_page_to_project(project_id: &str, title: &str, content: &str) -> Result<String, String> {
+    insert_page_at(project_id, title, content, usize::MAX)
+}
+
+/// Create a page at `index` in the page order (clamped to the end).
+/// Returns the new page's filename.
+pub fn insert_page_at(project_id: &str, title: &str, content: &str, index: usize) -> Result<String, String> {
     let mut meta = load_project(project_id)?;
+    let index = index.min(meta.page_order.len());
 
-    // Generate page filename
-    let page_num = meta.page_order.len() + 1;
-    let page_name = format!("{:02}-{}.md", page_num, slug::slugify(title));
+    // Generate page filename, never reusing an existing file
+    let page_name = available_page_name(project_id, &page_stem(index + 1, title))?;
 
     // Save the page content
     save_page_content(project_id, &page_name, content)?;
 
     // Update page order
-    meta.page_order.push(page_name.clone());
+    meta.page_order.insert(index, page_name.clone());
     meta.updated_at = Utc::now();
     save_project(&meta)?;
 
     Ok(page_name)
 }
 
-/// Import a folder of markdown files as a new project
-pub fn import_folder_as_project(folder_path: &str, title: &str, description: &str) -> Result<ProjectMeta, String> {
-    let folder = std::path::Path::new(folder_path);
-
-    if !folder.exists() || !folder.is_dir() {
-        return Err("Invalid folder path".to_string());
+/// Filename stem for a page: its position plus the slugified title.
+/// `slugify` transliterates non-Latin scripts ("Математика" becomes
+/// "matematika"); titles that still slugify to nothing get a random stem.
+fn page_stem(page_num: usize, title: &str) -> String {
+    let slug = slug::slugify(title);
+    if slug.is_empty() {
+        format!("{:02}-page-{}", page_num, &Uuid::new_v4().simple().to_string()[..8])
+    } else {
+        format!("{:02}-{}", page_num, slug)
     }
+}
 
-    // Collect all markdown files
-    let mut md_files: Vec<_> = fs::read_dir(folder)
-        .map_err(|e| format!("Failed to read folder: {}", e))?
-        .filter_map(|entry| entry.ok())
-        .filter(|entry| {
-            let path = entry.path();
-            path.is_file() && path.extension().map_or(false, |ext| ext == "md")
-        })
-        .collect();
+/// Pick a `<stem>.md` filename not already used in the project's pages
+/// directory, appending `-2`, `-3`, ... to the stem on collision
+fn available_page_name(project_id: &str, stem: &str) -> Result<String, String> {
+    let pages_dir = get_project_dir(project_id)?.join("pages");
 
-    if md_files.is_empty() {
-        return Err("No markdown files found in folder".to_string());
+    let mut page_name = format!("{}.md", stem);
+    let mut suffix = 2;
+    while pages_dir.join(&page_name).exists() {
+        page_name = format!("{}-{}.md", stem, suffix);
+        suffix += 1;
     }
 
-    // Sort by filename to maintain order
-    md_files.sort_by(|a, b| a.file_name().cmp(&b.file_name()));
-
-    // Create new project
-    let meta = create_new_project(title, description)?;
+    Ok(page_name)
+}
 
-    // Import each markdown file
-    for (index, entry) in md_files.iter().enumerate() {
-        let file_path = entry.path();
-        let content = fs::read_to_string(&file_path)
-            .map_err(|e| format!("Failed to read file {:?}: {}", file_path, e))?;
+/// Rename a page to a filename generated from `new_title`, keeping its
+/// position in the page order. Returns the new filename.
+pub fn rename_page(project_id: &str, old_name: &str, new_title: &str) -> Result<String, String> {
+    let mut meta = load_project(project_id)?;
+    let index = meta.page_order.iter()
+        .position(|p| p == old_name)
+        .ok_or_else(|| format!("Page '{}' not found in project", old_name))?;
 
-        // Extract title from filename or first heading
-        let file_stem = file_path.file_stem()
-            .and_then(|s| s.to_str())
-            .unwrap_or("untitled");
-
-        // Try to extract title from first # heading, otherwise use filename
-        let page_title = content.lines()
-            .find(|line| line.starts_with("# "))
-            .map(|line| line.trim_start_matches("# ").to_string())
-            .unwrap_or_else(|| {
-                // Clean up filename: remove leading numbers and dashes
-                let cleaned = file_stem
-                    .trim_start_matches(|c: char| c.is_numeric() || c == '-' || c == '_')
-                    .replace('-', " ")
-                    .replace('_', " ");
-                if cleaned.is_empty() { file_stem.to_string() } else { cleaned }
-            });
+    let stem = page_stem(index + 1, new_title);
+    if format!("{}.md", stem) == old_name {
+        return Ok(old_name.to_string());
+    }
+    let new_name = available_page_name(project_id, &stem)?;
 
-        // Generate page filename with order prefix
-        let page_name = format!("{:02}-{}.md", index + 1, slug::slugify(&page_title));
+    let pages_dir = get_project_dir(project_id)?.join("pages");
+    fs::rename(pages_dir.join(old_name), pages_dir.join(&new_name))
+        .map_err(|e| format!("Failed to rename page: {}", e))?;
+
+    // Keep the page's history with it
+    let history_dir = page_history_dir(project_id, old_name)?;
+    if history_dir.exists() {
+        fs::rename(&history_dir, page_history_dir(project_id, &new_name)?)
+            .map_err(|e| format!("Failed to move page history: {}", e))?;
+    }
 
-        // Save page content
-        let pages_dir = get_project_dir(&meta.id)?.join("pages");
-        if !pages_dir.exists() {
-            fs::create_dir_all(&pages_dir).map_err(|e| e.to_string())?;
+    // And its annotations
+    let mut annotations = list_annotations(project_id, None)?;
+    if annotations.iter().any(|a| a.page_name == old_name) {
+        for annotation in annotations.iter_mut().filter(|a| a.page_name == old_name) {
+            annotation.page_name = new_name.clone();
         }
-        fs::write(pages_dir.join(&page_name), &content)
-            .map_err(|e| format!("Failed to write page: {}", e))?;
-
-        // Update project meta with page
-        let mut updated_meta = load_project(&meta.id)?;
-        updated_meta.page_order.push(page_name);
-        updated_meta.updated_at = Utc::now();
-        save_project(&updated_meta)?;
+        save_annotations(project_id, &annotations)?;
     }
 
-    // Return the final project meta
-    load_project(&meta.id)
+    meta.page_order[index] = new_name.clone();
+    meta.updated_at = Utc::now();
+    save_project(&meta)?;
+
+    Ok(new_name)
 }
 
-// ============================================================================
-// Chat Session Functions
-// ============================================================================
+/// Delete a page file and drop it from the project's page order
+pub fn delete_page(project_id: &str, page_name: &str) -> Result<ProjectMeta, String> {
+    let mut meta = load_project(project_id)?;
+    if !meta.page_order.iter().any(|p| p == page_name) {
+        return Err(format!("Page '{}' not found in project", page_name));
+    }
 
-pub fn get_chats_dir(project_id: &str) -> Result<PathBuf, String> {
-    let chats_dir = get_project_dir(project_id)?.join("chats");
+    let page_path = get_project_dir(project_id)?.join("pages").join(page_name);
+    if page_path.exists() {
+        fs::remove_file(&page_path)
+            .map_err(|e| format!("Failed to delete page: {}", e))?;
+    }
 
-    if !chats_dir.exists() {
-        fs::create_dir_all(&chats_dir).map_err(|e| e.to_string())?;
+    let history_dir = page_history_dir(project_id, page_name)?;
+    if history_dir.exists() {
+        fs::remove_dir_all(&history_dir).map_err(|e| e.to_string())?;
     }
 
-    Ok(chats_dir)
+    let mut annotations = list_annotations(project_id, None)?;
+    let count = annotations.len();
+    annotations.retain(|a| a.page_name != page_name);
+    if annotations.len() != count {
+        save_annotations(project_id, &annotations)?;
+    }
+
+    meta.page_order.retain(|p| p != page_name);
+    meta.updated_at = Utc::now();
+    save_project(&meta)?;
+
+    Ok(meta)
 }
 
-pub fn list_chat_sessions(project_id: &str) -> Result<Vec<ChatSessionListItem>, String> {
-    let chats_dir = get_chats_dir(project_id)?;
-    let mut sessions = Vec::new();
+/// Set a new page order. The order must list exactly the project's current
+/// pages so a reorder can never drop (and orphan) a page.
+pub fn reorder_pages(project_id: &str, order: Vec<String>) -> Result<ProjectMeta, String> {
+    let mut meta = load_project(project_id)?;
 
-    if let Ok(entries) = fs::read_dir(&chats_dir) {
-        for entry in entries.flatten() {
-            let path = entry.path();
-            if path.extension().map_or(false, |ext| ext == "json") {
-                if let Ok(content) = fs::read_to_string(&path) {
-                    if let Ok(session) = serde_json::from_str::<ChatSession>(&content) {
-                        sessions.push(ChatSessionListItem::from(&session));
-                    }
-                }
-            }
-        }
+    let mut current = meta.page_order.clone();
+    let mut requested = order.clone();
+    current.sort();
+    requested.sort();
+    if current != requested {
+        return Err("Page order must contain exactly the project's current pages".to_string());
     }
 
-    // Sort by updated_at descending
-    sessions.sort_by(|a, b| b.updated_at.cmp(&a.updated_at));
+    meta.page_order = order;
+    meta.updated_at = Utc::now();
+    save_project(&meta)?;
 
-    Ok(sessions)
+    Ok(meta)
 }
 
-pub fn create_chat_session(project_id: &str, title: &str) -> Result<ChatSession, String> {
-    let id = Uuid::new_v4().to_string();
+/// Merge two pages into a new page titled `merged_title`, placed where the
+/// first page was. Both originals are removed; their version history moves
+/// to the merged page along with a final snapshot of each.
+pub fn merge_pages(
+    project_id: &str,
+    first_page: &str,
+    second_page: &str,
+    merged_title: &str,
+) -> Result<ProjectMeta, String> {
+    if first_page == second_page {
+        return Err("Cannot merge a page with itself".to_string());
+    }
+
+    let mut meta = load_project(project_id)?;
+    let first_index = meta.page_order.iter()
+        .position(|p| p == first_page)
+        .ok_or_else(|| format!("Page '{}' not found in project", first_page))?;
+    let second_index = meta.page_order.iter()
+        .position(|p| p == second_page)
+        .ok_or_else(|| format!("Page '{}' not found in project", second_page))?;
+
+    let first_content = load_page_content(project_id, first_page)?;
+    let second_content = load_page_content(project_id, second_page)?;
+    let merged = format!("{}\n\n{}", first_content.trim_end(), second_content.trim_start());
+
+    let merged_name = available_page_name(project_id, &page_stem(first_index + 1, merged_title))?;
+    let pages_dir = get_project_dir(project_id)?.join("pages");
+    write_atomic(pages_dir.join(&merged_name), &merged)
+        .map_err(|e| format!("Failed to write page: {}", e))?;
+
+    // Carry both pages' history over, then snapshot each original as it was
+    let merged_history = page_history_dir(project_id, &merged_name)?;
+    for page_name in [first_page, second_page] {
+        let history_dir = page_history_dir(project_id, page_name)?;
+        if !history_dir.exists() {
+            continue;
+        }
+        fs::create_dir_all(&merged_history).map_err(|e| e.to_string())?;
+        for stem in page_version_stems(&history_dir) {
+            let version = format!("{}.md", stem);
+            if !merged_history.join(&version).exists() {
+                fs::rename(history_dir.join(&version), merged_history.join(&version))
+                    .map_err(|e| format!("Failed to move page history: {}", e))?;
+            }
+        }
+        fs::remove_dir_all(&history_dir).map_err(|e| e.to_string())?;
+    }
     let now = Utc::now();
+    record_page_version_at(project_id, &merged_name, &first_content, now)?;
+    record_page_version_at(project_id, &merged_name, &second_content, now + chrono::Duration::milliseconds(1))?;
+
+    for page_name in [first_page, second_page] {
+        let page_path = pages_dir.join(page_name);
+        if page_path.exists() {
+            fs::remove_file(&page_path)
+                .map_err(|e| format!("Failed to delete page: {}", e))?;
+        }
+    }
 
-    let session = ChatSession {
-        id: id.clone(),
-        project_id: project_id.to_string(),
-        title: title.to_string(),
-        messages: Vec::new(),
-        created_at: now,
-        updated_at: now,
-    };
+    meta.page_order.retain(|p| p != first_page && p != second_page);
+    let index = if second_index < first_index { first_index - 1 } else { first_index };
+    meta.page_order.insert(index, merged_name);
+    meta.updated_at = Utc::now();
+    save_project(&meta)?;
 
-    save_chat_session(&session)?;
-    Ok(session)
+    Ok(meta)
 }
 
-pub fn load_chat_session(project_id: &str, session_id: &str) -> Result<ChatSession, String> {
-    let session_path = get_chats_dir(project_id)?.join(format!("{}.json", session_id));
-    let content = fs::read_to_string(&session_path)
-        .map_err(|e| format!("Failed to read chat session: {}", e))?;
-    serde_json::from_str(&content)
-        .map_err(|e| format!("Failed to parse chat session: {}", e))
+/// Byte offset of the line holding the `## ` heading with the given text,
+/// ignoring lines inside fenced code blocks
+fn find_section_heading(content: &str, heading: &str) -> Option<usize> {
+    let heading = heading.trim().trim_start_matches("## ").trim();
+    let mut offset = 0;
+    let mut in_fence = false;
+
+    for line in content.split_inclusive('\n') {
+        let trimmed = line.trim();
+        if trimmed.starts_with("```") {
+            in_fence = !in_fence;
+        } else if !in_fence && trimmed.strip_prefix("## ").is_some_and(|text| text.trim() == heading) {
+            return Some(offset);
+        }
+        offset += line.len();
+    }
+
+    None
 }
 
-pub fn save_chat_session(session: &ChatSession) -> Result<(), String> {
-    let chats_dir = get_chats_dir(&session.project_id)?;
-    let session_path = chats_dir.join(format!("{}.json", session.id));
+/// Split a page in two at a `## ` heading: everything before the heading
+/// and everything from it onward become consecutive pages replacing the
+/// original. The original's history stays with the first part.
+pub fn split_page(project_id: &str, page_name: &str, split_heading: &str) -> Result<ProjectMeta, String> {
+    let mut meta = load_project(project_id)?;
+    let index = meta.page_order.iter()
+        .position(|p| p == page_name)
+        .ok_or_else(|| format!("Page '{}' not found in project", page_name))?;
 
-    let content = serde_json::to_string_pretty(session)
-        .map_err(|e| format!("Failed to serialize chat session: {}", e))?;
-    fs::write(&session_path, content)
-        .map_err(|e| format!("Failed to write chat session: {}", e))?;
+    let content = load_page_content(project_id, page_name)?;
+    let split_at = find_section_heading(&content, split_heading)
+        .ok_or_else(|| format!("Heading '## {}' not found in '{}'", split_heading.trim().trim_start_matches("## "), page_name))?;
 
-    Ok(())
-}
+    let (before, after) = content.split_at(split_at);
+    if before.trim().is_empty() {
+        return Err("There is no content before that heading to split off".to_string());
+    }
 
-#[allow(dead_code)]
-pub fn add_message_to_session(
-    project_id: &str,
-    session_id: &str,
-    role: &str,
-    content: &str
-) -> Result<ChatSession, String> {
-    let mut session = load_chat_session(project_id, session_id)?;
+    let first_title = imported_page_title(Path::new(page_name), before);
+    let second_title = extract_page_title(after)
+        .unwrap_or_else(|| split_heading.trim().trim_start_matches("## ").to_string());
 
-    session.messages.push(ChatMessage {
-        role: role.to_string(),
-        content: content.to_string(),
-        timestamp: Utc::now(),
-    });
-    session.updated_at = Utc::now();
+    let pages_dir = get_project_dir(project_id)?.join("pages");
+    let first_name = available_page_name(project_id, &page_stem(index + 1, &first_title))?;
+    write_atomic(pages_dir.join(&first_name), format!("{}\n", before.trim_end()))
+        .map_err(|e| format!("Failed to write page: {}", e))?;
+    let second_name = available_page_name(project_id, &page_stem(index + 2, &second_title))?;
+    write_atomic(pages_dir.join(&second_name), after)
+        .map_err(|e| format!("Failed to write page: {}", e))?;
 
-    // Update title from first user message if it's still "New Chat"
-    if session.title == "New Chat" && role == "user" {
+    // Keep the history (plus the unsplit page) with the first part
+    let history_dir = page_history_dir(project_id, page_name)?;
+    if history_dir.exists() {
+        fs::rename(&history_dir, page_history_dir(project_id, &first_name)?)
+            .map_err(|e| format!("Failed to move page history: {}", e))?;
+    }
+    record_page_version(project_id, &first_name, &content)?;
+
+    fs::remove_file(pages_dir.join(page_name))
+        .map_err(|e| format!("Failed to delete page: {}", e))?;
+
+    meta.page_order.splice(index..=index, [first_name, second_name]);
+    meta.updated_at = Utc::now();
+    save_project(&meta)?;
+
+    Ok(meta)
+}
+
+/// Update a project's title and description
+pub fn update_project(project_id: &str, title: &str, description: &str) -> Result<ProjectMeta, String> {
+    let title = title.trim();
+    if title.is_empty() {
+        return Err("Project title cannot be empty".to_string());
+    }
+
+    let mut meta = load_project(project_id)?;
+    meta.title = title.to_string();
+    meta.description = description.trim().to_string();
+    meta.updated_at = Utc::now();
+    save_project(&meta)?;
+
+    Ok(meta)
+}
+
+/// Replace a project's tags. Blank tags and case-insensitive duplicates are
+/// dropped.
+pub fn set_project_tags(project_id: &str, tags: Vec<String>) -> Result<ProjectMeta, String> {
+    let mut cleaned: Vec<String> = Vec::new();
+    for tag in tags {
+        let tag = tag.trim();
+        if !tag.is_empty() && !cleaned.iter().any(|t| t.eq_ignore_ascii_case(tag)) {
+            cleaned.push(tag.to_string());
+        }
+    }
+
+    let mut meta = load_project(project_id)?;
+    meta.tags = cleaned;
+    meta.updated_at = Utc::now();
+    save_project(&meta)?;
+
+    Ok(meta)
+}
+
+/// Set (or clear, with None/blank) the model this project uses instead of
+/// the configured one
+pub fn set_project_model(project_id: &str, model: Option<String>) -> Result<ProjectMeta, String> {
+    let mut meta = load_project(project_id)?;
+    meta.model_override = model
+        .map(|m| m.trim().to_string())
+        .filter(|m| !m.is_empty());
+    meta.updated_at = Utc::now();
+    save_project(&meta)?;
+
+    Ok(meta)
+}
+
+/// Set (or clear, with None/blank) the project's export cover image. Takes a
+/// path to an image file or a `data:` URI.
+pub fn set_project_cover(project_id: &str, cover: Option<String>) -> Result<ProjectMeta, String> {
+    let cover = cover
+        .map(|c| c.trim().to_string())
+        .filter(|c| !c.is_empty());
+    if let Some(path) = cover.as_deref().filter(|c| !c.starts_with("data:")) {
+        if !Path::new(path).is_file() {
+            return Err(format!("Cover image not found: {}", path));
+        }
+    }
+
+    let mut meta = load_project(project_id)?;
+    meta.cover_image = cover;
+    meta.updated_at = Utc::now();
+    save_project(&meta)?;
+
+    Ok(meta)
+}
+
+/// Set (or clear, with None/blank) the project's custom AI instructions
+pub fn set_custom_instructions(project_id: &str, instructions: Option<String>) -> Result<ProjectMeta, String> {
+    let mut meta = load_project(project_id)?;
+    meta.custom_instructions = instructions
+        .map(|i| i.trim().to_string())
+        .filter(|i| !i.is_empty());
+    meta.updated_at = Utc::now();
+    save_project(&meta)?;
+
+    Ok(meta)
+}
+
+/// Text of the first `# ` heading in a page, if it has one
+pub fn extract_page_title(content: &str) -> Option<String> {
+    content.lines()
+        .find(|line| line.starts_with("# "))
+        .map(|line| line.trim_start_matches("# ").trim().to_string())
+}
+
+/// Title for an imported markdown file: its first `# ` heading, otherwise a
+/// cleaned-up version of the filename
+pub fn imported_page_title(file_path: &Path, content: &str) -> String {
+    let file_stem = file_path.file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or("untitled");
+
+    extract_page_title(content)
+        .unwrap_or_else(|| {
+            // Clean up filename: remove leading numbers and dashes
+            let cleaned = file_stem
+                .trim_start_matches(|c: char| c.is_numeric() || c == '-' || c == '_')
+                .replace('-', " ")
+                .replace('_', " ");
+            if cleaned.is_empty() { file_stem.to_string() } else { cleaned }
+        })
+}
+
+/// Import one markdown file as a page of an existing project, or as a new
+/// single-page project when `project_id` is None
+pub fn import_markdown_file(project_id: Option<&str>, file_path: &str) -> Result<ImportedPage, String> {
+    let path = Path::new(file_path);
+    if !path.is_file() {
+        return Err("Invalid file path".to_string());
+    }
+
+    let content = fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read file {:?}: {}", path, e))?;
+    let title = imported_page_title(path, &content);
+
+    let project_id = match project_id {
+        Some(id) => id.to_string(),
+        None => create_new_project(&title, "")?.id,
+    };
+    let page_name = add_page_to_project(&project_id, &title, &content)?;
+
+    Ok(ImportedPage { project_id, page_name })
+}
+
+/// A markdown file found for import, with the subfolders it sits in
+struct ImportFile {
+    path: PathBuf,
+    folders: Vec<String>,
+}
+
+/// Collect markdown files in `dir` sorted by name. With `recursive`,
+/// subfolders are walked depth-first in the same order; hidden folders are
+/// skipped and each folder is visited once so symlink loops can't recurse.
+fn collect_import_files(
+    dir: &Path,
+    recursive: bool,
+    folders: &[String],
+    visited: &mut HashSet<PathBuf>,
+    files: &mut Vec<ImportFile>,
+) -> Result<(), String> {
+    let canonical = dir.canonicalize()
+        .map_err(|e| format!("Failed to read folder: {}", e))?;
+    if !visited.insert(canonical) {
+        return Ok(());
+    }
+
+    let mut entries: Vec<_> = fs::read_dir(dir)
+        .map_err(|e| format!("Failed to read folder: {}", e))?
+        .filter_map(|entry| entry.ok())
+        .collect();
+    entries.sort_by_key(|entry| entry.file_name());
+
+    for entry in entries {
+        let path = entry.path();
+        if path.is_file() && path.extension().is_some_and(|ext| ext == "md") {
+            files.push(ImportFile { path, folders: folders.to_vec() });
+        } else if recursive && path.is_dir() {
+            let name = entry.file_name().to_string_lossy().to_string();
+            if name.starts_with('.') {
+                continue;
+            }
+            let mut sub_folders = folders.to_vec();
+            sub_folders.push(name);
+            collect_import_files(&path, recursive, &sub_folders, visited, files)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Collect a folder's markdown files for import, erroring if there are none
+fn collect_folder_for_import(folder_path: &str, recursive: bool) -> Result<Vec<ImportFile>, String> {
+    let folder = std::path::Path::new(folder_path);
+
+    if !folder.exists() || !folder.is_dir() {
+        return Err("Invalid folder path".to_string());
+    }
+
+    // Collect all markdown files, sorted by filename to maintain order
+    let mut md_files = Vec::new();
+    collect_import_files(folder, recursive, &[], &mut HashSet::new(), &mut md_files)?;
+
+    if md_files.is_empty() {
+        return Err("No markdown files found in folder".to_string());
+    }
+
+    Ok(md_files)
+}
+
+/// Append imported files to the end of a project's page order, numbering
+/// them on from the existing pages and never overwriting an existing file
+fn append_import_files(project_id: &str, files: &[ImportFile]) -> Result<ProjectMeta, String> {
+    let mut meta = load_project(project_id)?;
+
+    let pages_dir = get_project_dir(project_id)?.join("pages");
+    if !pages_dir.exists() {
+        fs::create_dir_all(&pages_dir).map_err(|e| e.to_string())?;
+    }
+
+    for file in files {
+        let file_path = &file.path;
+        let content = fs::read_to_string(file_path)
+            .map_err(|e| format!("Failed to read file {:?}: {}", file_path, e))?;
+
+        let page_title = imported_page_title(file_path, &content);
+
+        // Keep files from subfolders grouped and recognisable
+        let page_title = if file.folders.is_empty() {
+            page_title
+        } else {
+            format!("{} - {}", file.folders.join(" - "), page_title)
+        };
+
+        // Generate page filename with order prefix
+        let page_name = available_page_name(project_id, &page_stem(meta.page_order.len() + 1, &page_title))?;
+
+        write_atomic(pages_dir.join(&page_name), &content)
+            .map_err(|e| format!("Failed to write page: {}", e))?;
+
+        // Record each page as it lands so a failure part-way keeps what was imported
+        meta.page_order.push(page_name);
+        meta.updated_at = Utc::now();
+        save_project(&meta)?;
+    }
+
+    Ok(meta)
+}
+
+/// Import a folder of markdown files as a new project. With `recursive`,
+/// files in subfolders are included too, flattened into the page order.
+pub fn import_folder_as_project(
+    folder_path: &str,
+    title: &str,
+    description: &str,
+    recursive: bool,
+) -> Result<ProjectMeta, String> {
+    let md_files = collect_folder_for_import(folder_path, recursive)?;
+
+    // Create new project
+    let meta = create_new_project(title, description)?;
+
+    append_import_files(&meta.id, &md_files)
+}
+
+/// Import a folder of markdown files onto the end of an existing project
+pub fn append_folder_to_project(
+    project_id: &str,
+    folder_path: &str,
+    recursive: bool,
+) -> Result<ProjectMeta, String> {
+    let md_files = collect_folder_for_import(folder_path, recursive)?;
+
+    append_import_files(project_id, &md_files)
+}
+
+/// Separator between pages in a single-file markdown export
+const MARKDOWN_PAGE_SEPARATOR: &str = "\n\n---\n\n";
+
+/// Export a project's pages as markdown. If `output_path` is an existing
+/// directory, each page is written there as its own numbered `.md` file
+/// (the inverse of `import_folder_as_project`); otherwise all pages are
+/// joined into one file under the project title as an H1.
+pub fn export_project_to_markdown(project_id: &str, output_path: &str) -> Result<(), String> {
+    let meta = load_project(project_id)?;
+    let output = Path::new(output_path);
+
+    if output.is_dir() {
+        for (index, page_name) in meta.page_order.iter().enumerate() {
+            let content = load_page_content(project_id, page_name)?;
+            // Renumber by position, since reordering doesn't rename files
+            let name = page_name.trim_start_matches(|c: char| c.is_ascii_digit() || c == '-');
+            let name = if name.is_empty() { page_name.as_str() } else { name };
+            fs::write(output.join(format!("{:02}-{}", index + 1, name)), content)
+                .map_err(|e| format!("Failed to write markdown: {}", e))?;
+        }
+        return Ok(());
+    }
+
+    let mut pages = Vec::with_capacity(meta.page_order.len());
+    for page_name in &meta.page_order {
+        pages.push(load_page_content(project_id, page_name)?.trim().to_string());
+    }
+
+    let markdown = format!("# {}\n\n{}\n", meta.title, pages.join(MARKDOWN_PAGE_SEPARATOR));
+    fs::write(output, markdown)
+        .map_err(|e| format!("Failed to write markdown: {}", e))
+}
+
+// ============================================================================
+// Cross References
+// ============================================================================
+
+/// Titles shorter than this are too likely to match ordinary prose
+const MIN_CROSS_REFERENCE_CHARS: usize = 4;
+
+/// `#anchor` a chapter title is reachable at. The PDF export gives chapter
+/// titles matching heading ids.
+pub fn chapter_anchor(title: &str) -> String {
+    slug::slugify(title)
+}
+
+/// The part of a chapter title to look for in other pages, without a
+/// "Chapter 3:" style numbering prefix
+fn cross_reference_phrase(title: &str) -> String {
+    let numbering = Regex::new(r"(?i)^(chapter|part|lesson|module|unit)\s+\d+\s*[:.\-–—]\s*").unwrap();
+    numbering.replace(title, "").replace(['*', '_', '`'], "").trim().to_string()
+}
+
+/// Link the first mention of each other chapter's title on every page to
+/// that chapter, unless the page already links there. Headings, code and
+/// existing links are left alone. Returns how many links were added.
+pub fn link_cross_references(project_id: &str) -> Result<usize, String> {
+    let meta = load_project(project_id)?;
+
+    let mut pages = Vec::with_capacity(meta.page_order.len());
+    for page_name in &meta.page_order {
+        pages.push((page_name.clone(), load_page_content(project_id, page_name)?));
+    }
+
+    // (page, phrase, anchor), longest phrase first so "Advanced Ownership"
+    // is linked before "Ownership" can claim part of it
+    let mut targets: Vec<(String, String, String)> = pages.iter()
+        .filter_map(|(page_name, content)| {
+            let title = extract_page_title(content)?;
+            let phrase = cross_reference_phrase(&title);
+            let anchor = chapter_anchor(&title);
+            (phrase.chars().count() >= MIN_CROSS_REFERENCE_CHARS && !anchor.is_empty())
+                .then(|| (page_name.clone(), phrase, anchor))
+        })
+        .collect();
+    targets.sort_by_key(|(_, phrase, _)| std::cmp::Reverse(phrase.chars().count()));
+
+    let mut added = 0;
+    for (page_name, content) in &pages {
+        let mut updated = content.clone();
+        for (target_page, phrase, anchor) in &targets {
+            if target_page == page_name || updated.contains(&format!("](#{})", anchor)) {
+                continue;
+            }
+            if let Some(linked) = link_first_mention(&updated, phrase, anchor) {
+                updated = linked;
+                added += 1;
+            }
+        }
+        if updated != *content {
+            save_page_content(project_id, page_name, &updated)?;
+        }
+    }
+
+    Ok(added)
+}
+
+/// Wrap the first whole-word, case-insensitive mention of `phrase` in a link
+/// to `#anchor`, skipping headings, code and text already in a link
+fn link_first_mention(content: &str, phrase: &str, anchor: &str) -> Option<String> {
+    let pattern = Regex::new(&format!(r"(?i)\b{}\b", regex::escape(phrase))).ok()?;
+    let protected = Regex::new(r"`[^`]*`|!?\[[^\]]*\](\([^)]*\))?|<[^>\n]+>|https?://\S+").unwrap();
+
+    let mut offset = 0;
+    let mut in_fence = false;
+    for line in content.split_inclusive('\n') {
+        let line_start = offset;
+        offset += line.len();
+
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            in_fence = !in_fence;
+            continue;
+        }
+        if in_fence || trimmed.starts_with('#') {
+            continue;
+        }
+
+        let protected_ranges: Vec<(usize, usize)> = protected.find_iter(line)
+            .map(|m| (m.start(), m.end()))
+            .collect();
+        let mention = pattern.find_iter(line)
+            .find(|m| !protected_ranges.iter().any(|&(start, end)| m.start() < end && m.end() > start));
+
+        if let Some(m) = mention {
+            return Some(format!(
+                "{}[{}](#{}){}",
+                &content[..line_start + m.start()],
+                m.as_str(),
+                anchor,
+                &content[line_start + m.end()..]
+            ));
+        }
+    }
+
+    None
+}
+
+// ============================================================================
+// Page History
+// ============================================================================
+
+/// How many previous versions are kept per page
+const PAGE_HISTORY_LIMIT: usize = 20;
+
+/// Filename-safe timestamp that also sorts chronologically
+const PAGE_VERSION_FORMAT: &str = "%Y%m%dT%H%M%S%3fZ";
+
+fn page_history_dir(project_id: &str, page_name: &str) -> Result<PathBuf, String> {
+    Ok(get_project_dir(project_id)?.join("pages").join(".history").join(page_name))
+}
+
+/// Store `content` as the newest version of a page, dropping the oldest
+/// versions beyond `PAGE_HISTORY_LIMIT`
+fn record_page_version(project_id: &str, page_name: &str, content: &str) -> Result<(), String> {
+    record_page_version_at(project_id, page_name, content, Utc::now())
+}
+
+/// Store `content` as a version of a page saved at `saved_at`
+fn record_page_version_at(
+    project_id: &str,
+    page_name: &str,
+    content: &str,
+    saved_at: chrono::DateTime<Utc>,
+) -> Result<(), String> {
+    let history_dir = page_history_dir(project_id, page_name)?;
+    if !history_dir.exists() {
+        fs::create_dir_all(&history_dir).map_err(|e| e.to_string())?;
+    }
+
+    let timestamp = saved_at.format(PAGE_VERSION_FORMAT).to_string();
+    write_atomic(history_dir.join(format!("{}.md", timestamp)), content)
+        .map_err(|e| format!("Failed to write page version: {}", e))?;
+
+    let versions = page_version_stems(&history_dir);
+    if versions.len() > PAGE_HISTORY_LIMIT {
+        for stem in &versions[..versions.len() - PAGE_HISTORY_LIMIT] {
+            let _ = fs::remove_file(history_dir.join(format!("{}.md", stem)));
+        }
+    }
+
+    Ok(())
+}
+
+/// Version timestamps in a history dir, oldest first
+fn page_version_stems(history_dir: &Path) -> Vec<String> {
+    let mut stems: Vec<String> = fs::read_dir(history_dir)
+        .map(|entries| {
+            entries.flatten()
+                .map(|e| e.path())
+                .filter(|p| p.extension().is_some_and(|ext| ext == "md"))
+                .filter_map(|p| p.file_stem().map(|s| s.to_string_lossy().to_string()))
+                .collect()
+        })
+        .unwrap_or_default();
+    stems.sort();
+    stems
+}
+
+/// Previous versions of a page, newest first
+pub fn list_page_versions(project_id: &str, page_name: &str) -> Result<Vec<PageVersion>, String> {
+    let history_dir = page_history_dir(project_id, page_name)?;
+
+    let versions = page_version_stems(&history_dir)
+        .into_iter()
+        .rev()
+        .filter_map(|timestamp| {
+            let saved_at = chrono::NaiveDateTime::parse_from_str(&timestamp, PAGE_VERSION_FORMAT)
+                .ok()?
+                .and_utc();
+            let size = fs::metadata(history_dir.join(format!("{}.md", timestamp)))
+                .map(|m| m.len())
+                .unwrap_or(0);
+            Some(PageVersion { timestamp, saved_at, size })
+        })
+        .collect();
+
+    Ok(versions)
+}
+
+/// Replace a page with one of its previous versions. The current content is
+/// recorded in the history first, so a restore can itself be undone.
+pub fn restore_page_version(project_id: &str, page_name: &str, timestamp: &str) -> Result<String, String> {
+    if timestamp.contains(['/', '\\', '.']) {
+        return Err(format!("Invalid page version '{}'", timestamp));
+    }
+
+    let version_path = page_history_dir(project_id, page_name)?.join(format!("{}.md", timestamp));
+    let content = fs::read_to_string(&version_path)
+        .map_err(|e| format!("Failed to read page version '{}': {}", timestamp, e))?;
+
+    save_page_content(project_id, page_name, &content)?;
+
+    Ok(content)
+}
+
+// ============================================================================
+// Expansion Records
+// ============================================================================
+
+fn expansions_path(project_id: &str) -> Result<PathBuf, String> {
+    Ok(get_project_dir(project_id)?.join("expansions.json"))
+}
+
+/// Saved expansions for a project, oldest first
+pub fn list_expansions(project_id: &str) -> Result<Vec<ExpansionRecord>, String> {
+    let path = expansions_path(project_id)?;
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+
+    let content = fs::read_to_string(&path)
+        .map_err(|e| format!("Failed to read expansions: {}", e))?;
+    serde_json::from_str(&content)
+        .map_err(|e| format!("Failed to parse expansions: {}", e))
+}
+
+fn save_expansions(project_id: &str, records: &[ExpansionRecord]) -> Result<(), String> {
+    let content = serde_json::to_string_pretty(records)
+        .map_err(|e| format!("Failed to serialize expansions: {}", e))?;
+    write_atomic(expansions_path(project_id)?, content)
+        .map_err(|e| format!("Failed to write expansions: {}", e))
+}
+
+/// Record a saved expansion as the region where `updated` differs from
+/// `original` (everything between their common leading and trailing lines)
+pub fn record_expansion(
+    project_id: &str,
+    page_name: &str,
+    expansion_id: &str,
+    original: &str,
+    updated: &str,
+) -> Result<(), String> {
+    let old_lines: Vec<&str> = original.lines().collect();
+    let new_lines: Vec<&str> = updated.lines().collect();
+
+    let prefix = old_lines.iter()
+        .zip(&new_lines)
+        .take_while(|(a, b)| a == b)
+        .count();
+    let max_suffix = old_lines.len().min(new_lines.len()) - prefix;
+    let suffix = old_lines.iter().rev()
+        .zip(new_lines.iter().rev())
+        .take(max_suffix)
+        .take_while(|(a, b)| a == b)
+        .count();
+
+    let mut records = list_expansions(project_id)?;
+    records.push(ExpansionRecord {
+        expansion_id: expansion_id.to_string(),
+        page_name: page_name.to_string(),
+        start_line: prefix + 1,
+        inserted_content: new_lines[prefix..new_lines.len() - suffix].join("\n"),
+        replaced_content: old_lines[prefix..old_lines.len() - suffix].join("\n"),
+        previous_content: Some(original.to_string()),
+        created_at: Utc::now(),
+    });
+    save_expansions(project_id, &records)
+}
+
+/// Revert a saved expansion: its inserted lines are swapped back for the
+/// lines they replaced. They are looked for at the recorded position first,
+/// then anywhere in the page (edits above may have shifted them). Returns
+/// the updated page content.
+pub fn remove_expansion(project_id: &str, page_name: &str, expansion_id: &str) -> Result<String, String> {
+    let mut records = list_expansions(project_id)?;
+    let index = records.iter()
+        .position(|r| r.expansion_id == expansion_id && r.page_name == page_name)
+        .ok_or_else(|| format!("No expansion '{}' recorded for this page", expansion_id))?;
+    let record = records.remove(index);
+
+    let content = load_page_content(project_id, page_name)?;
+    let mut lines: Vec<&str> = content.lines().collect();
+    let inserted: Vec<&str> = if record.inserted_content.is_empty() {
+        Vec::new()
+    } else {
+        record.inserted_content.split('\n').collect()
+    };
+    let replaced: Vec<&str> = if record.replaced_content.is_empty() {
+        Vec::new()
+    } else {
+        record.replaced_content.split('\n').collect()
+    };
+
+    let recorded_start = record.start_line.saturating_sub(1);
+    let matches_at = |start: usize, lines: &[&str]| {
+        lines.get(start..start + inserted.len()) == Some(&inserted[..])
+    };
+    let start = if matches_at(recorded_start, &lines) {
+        recorded_start
+    } else {
+        let found: Vec<usize> = (0..=lines.len().saturating_sub(inserted.len()))
+            .filter(|&i| matches_at(i, &lines))
+            .collect();
+        match found.as_slice() {
+            [i] if !inserted.is_empty() => *i,
+            _ => return Err("The expanded text was edited since, so it can't be removed automatically".to_string()),
+        }
+    };
+
+    lines.splice(start..start + inserted.len(), replaced.iter().copied());
+    let mut updated = lines.join("\n");
+    if content.ends_with('\n') {
+        updated.push('\n');
+    }
+
+    save_page_content(project_id, page_name, &updated)?;
+
+    // Later expansions on the same page moved with the removed lines
+    let shift = replaced.len() as isize - inserted.len() as isize;
+    for other in records.iter_mut().filter(|r| r.page_name == page_name && r.start_line > start + 1) {
+        other.start_line = (other.start_line as isize + shift).max(1) as usize;
+    }
+    save_expansions(project_id, &records)?;
+
+    Ok(updated)
+}
+
+/// Undo the most recent expansion saved on a page by restoring the page as
+/// it was before it. Returns the restored content.
+pub fn undo_last_expansion(project_id: &str, page_name: &str) -> Result<String, String> {
+    let mut records = list_expansions(project_id)?;
+    let index = records.iter()
+        .rposition(|r| r.page_name == page_name)
+        .ok_or_else(|| "No expansions recorded for this page".to_string())?;
+
+    // Older records don't have the page content, so remove those line by line
+    let Some(previous) = records[index].previous_content.clone() else {
+        let expansion_id = records[index].expansion_id.clone();
+        return remove_expansion(project_id, page_name, &expansion_id);
+    };
+
+    records.remove(index);
+    save_page_content(project_id, page_name, &previous)?;
+    save_expansions(project_id, &records)?;
+
+    Ok(previous)
+}
+
+// ============================================================================
+// Annotations
+// ============================================================================
+
+fn annotations_path(project_id: &str) -> Result<PathBuf, String> {
+    Ok(get_project_dir(project_id)?.join("annotations.json"))
+}
+
+/// A project's annotations (only those on `page_name` when given), ordered
+/// by page and line
+pub fn list_annotations(project_id: &str, page_name: Option<&str>) -> Result<Vec<Annotation>, String> {
+    let path = annotations_path(project_id)?;
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+
+    let content = fs::read_to_string(&path)
+        .map_err(|e| format!("Failed to read annotations: {}", e))?;
+    let mut annotations: Vec<Annotation> = serde_json::from_str(&content)
+        .map_err(|e| format!("Failed to parse annotations: {}", e))?;
+
+    if let Some(page_name) = page_name {
+        annotations.retain(|a| a.page_name == page_name);
+    }
+    annotations.sort_by(|a, b| (&a.page_name, a.line).cmp(&(&b.page_name, b.line)));
+    Ok(annotations)
+}
+
+fn save_annotations(project_id: &str, annotations: &[Annotation]) -> Result<(), String> {
+    let content = serde_json::to_string_pretty(annotations)
+        .map_err(|e| format!("Failed to serialize annotations: {}", e))?;
+    write_atomic(annotations_path(project_id)?, content)
+        .map_err(|e| format!("Failed to write annotations: {}", e))
+}
+
+/// Attach a note to a line of a page. The page itself is not modified.
+pub fn add_annotation(project_id: &str, page_name: &str, line: usize, text: &str) -> Result<Annotation, String> {
+    let text = text.trim();
+    if text.is_empty() {
+        return Err("Annotation text cannot be empty".to_string());
+    }
+    if !load_project(project_id)?.page_order.iter().any(|p| p == page_name) {
+        return Err(format!("Page '{}' not found in project", page_name));
+    }
+
+    let annotation = Annotation {
+        id: Uuid::new_v4().to_string(),
+        page_name: page_name.to_string(),
+        line: line.max(1),
+        text: text.to_string(),
+        created_at: Utc::now(),
+    };
+
+    let mut annotations = list_annotations(project_id, None)?;
+    annotations.push(annotation.clone());
+    save_annotations(project_id, &annotations)?;
+
+    Ok(annotation)
+}
+
+pub fn delete_annotation(project_id: &str, annotation_id: &str) -> Result<(), String> {
+    let mut annotations = list_annotations(project_id, None)?;
+    let count = annotations.len();
+    annotations.retain(|a| a.id != annotation_id);
+    if annotations.len() == count {
+        return Err(format!("Annotation '{}' not found", annotation_id));
+    }
+    save_annotations(project_id, &annotations)
+}
+
+// ============================================================================
+// Chat Session Functions
+// ============================================================================
+
+pub fn get_chats_dir(project_id: &str) -> Result<PathBuf, String> {
+    let chats_dir = get_project_dir(project_id)?.join("chats");
+
+    if !chats_dir.exists() {
+        fs::create_dir_all(&chats_dir).map_err(|e| e.to_string())?;
+    }
+
+    Ok(chats_dir)
+}
+
+pub fn list_chat_sessions(project_id: &str) -> Result<Vec<ChatSessionListItem>, String> {
+    let chats_dir = get_chats_dir(project_id)?;
+    let mut sessions = Vec::new();
+
+    if let Ok(entries) = fs::read_dir(&chats_dir) {
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.extension().map_or(false, |ext| ext == "json") {
+                if let Ok(content) = fs::read_to_string(&path) {
+                    if let Ok(session) = serde_json::from_str::<ChatSession>(&content) {
+                        // Prefer the in-memory copy if it has unsaved messages
+                        let session = pending_chat_session(&session.project_id, &session.id)
+                            .unwrap_or(session);
+                        sessions.push(ChatSessionListItem::from(&session));
+                    }
+                }
+            }
+        }
+    }
+
+    // Sort by updated_at descending
+    sessions.sort_by(|a, b| b.updated_at.cmp(&a.updated_at));
+
+    Ok(sessions)
+}
+
+pub fn create_chat_session(project_id: &str, title: &str) -> Result<ChatSession, String> {
+    let id = Uuid::new_v4().to_string();
+    let now = Utc::now();
+
+    let session = ChatSession {
+        id: id.clone(),
+        project_id: project_id.to_string(),
+        title: title.to_string(),
+        messages: Vec::new(),
+        tool_actions: Vec::new(),
+        created_at: now,
+        updated_at: now,
+    };
+
+    save_chat_session(&session)?;
+    Ok(session)
+}
+
+pub fn load_chat_session(project_id: &str, session_id: &str) -> Result<ChatSession, String> {
+    if let Some(session) = pending_chat_session(project_id, session_id) {
+        return Ok(session);
+    }
+
+    let session_path = get_chats_dir(project_id)?.join(format!("{}.json", session_id));
+    let content = fs::read_to_string(&session_path)
+        .map_err(|e| format!("Failed to read chat session: {}", e))?;
+    serde_json::from_str(&content)
+        .map_err(|e| format!("Failed to parse chat session: {}", e))
+}
+
+pub fn save_chat_session(session: &ChatSession) -> Result<(), String> {
+    // A direct save supersedes anything still waiting in the batch queue
+    if let Ok(mut pending) = pending_chat_saves().lock() {
+        pending.remove(&pending_chat_key(&session.project_id, &session.id));
+    }
+
+    let chats_dir = get_chats_dir(&session.project_id)?;
+    let session_path = chats_dir.join(format!("{}.json", session.id));
+
+    let content = serde_json::to_string_pretty(session)
+        .map_err(|e| format!("Failed to serialize chat session: {}", e))?;
+    write_atomic(&session_path, content)
+        .map_err(|e| format!("Failed to write chat session: {}", e))?;
+
+    Ok(())
+}
+
+#[allow(dead_code)]
+pub fn add_message_to_session(
+    project_id: &str,
+    session_id: &str,
+    role: &str,
+    content: &str
+) -> Result<ChatSession, String> {
+    let mut session = load_chat_session(project_id, session_id)?;
+
+    session.messages.push(ChatMessage {
+        role: role.to_string(),
+        content: content.to_string(),
+        timestamp: Utc::now(),
+        interrupted: false,
+    });
+    session.updated_at = Utc::now();
+
+    // Update title from first user message if it's still "New Chat"
+    if session.title == "New Chat" && role == "user" {
         let title = content.chars().take(50).collect::<String>();
         session.title = if content.len() > 50 {
             format!("{}...", title)
@@ -329,14 +1588,328 @@ pub fn add_message_to_session(
         };
     }
 
-    save_chat_session(&session)?;
+    queue_chat_session_save(&session)?;
     Ok(session)
 }
 
 pub fn delete_chat_session(project_id: &str, session_id: &str) -> Result<(), String> {
+    if let Ok(mut pending) = pending_chat_saves().lock() {
+        pending.remove(&pending_chat_key(project_id, session_id));
+    }
+
     let session_path = get_chats_dir(project_id)?.join(format!("{}.json", session_id));
     if session_path.exists() {
         fs::remove_file(&session_path).map_err(|e| e.to_string())?;
     }
     Ok(())
 }
+
+// ============================================================================
+// Chat Session Save Batching
+// ============================================================================
+
+/// A chat session with messages that have not been written to disk yet
+struct PendingChatSave {
+    session: ChatSession,
+    unsaved_messages: usize,
+    last_flush: Instant,
+}
+
+fn pending_chat_saves() -> &'static Mutex<HashMap<String, PendingChatSave>> {
+    static PENDING: OnceLock<Mutex<HashMap<String, PendingChatSave>>> = OnceLock::new();
+    PENDING.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+fn pending_chat_key(project_id: &str, session_id: &str) -> String {
+    format!("{}/{}", project_id, session_id)
+}
+
+fn pending_chat_session(project_id: &str, session_id: &str) -> Option<ChatSession> {
+    let pending = pending_chat_saves().lock().ok()?;
+    pending
+        .get(&pending_chat_key(project_id, session_id))
+        .map(|p| p.session.clone())
+}
+
+/// Record a new message on a session, writing it to disk only once the
+/// configured batch size or interval is reached. Without batching configured
+/// this saves immediately, same as `save_chat_session`.
+pub fn queue_chat_session_save(session: &ChatSession) -> Result<(), String> {
+    let config = config_service::load_config().unwrap_or_default();
+    let batch_size = config.chat_save_batch_size.unwrap_or(1);
+    let interval = config.chat_save_interval_secs.map(Duration::from_secs);
+
+    if batch_size <= 1 && interval.is_none() {
+        return save_chat_session(session);
+    }
+
+    let mut pending = pending_chat_saves()
+        .lock()
+        .map_err(|_| "Chat save queue is poisoned".to_string())?;
+
+    let key = pending_chat_key(&session.project_id, &session.id);
+    let entry = pending.entry(key.clone()).or_insert_with(|| PendingChatSave {
+        session: session.clone(),
+        unsaved_messages: 0,
+        last_flush: Instant::now(),
+    });
+    entry.session = session.clone();
+    entry.unsaved_messages += 1;
+
+    let batch_full = batch_size > 1 && entry.unsaved_messages >= batch_size;
+    let interval_elapsed = interval.is_some_and(|i| entry.last_flush.elapsed() >= i);
+
+    if !(batch_full || interval_elapsed) {
+        return Ok(());
+    }
+
+    let entry = pending.remove(&key);
+    drop(pending);
+
+    match entry {
+        Some(entry) => save_chat_session(&entry.session),
+        None => Ok(()),
+    }
+}
+
+/// Write any unsaved messages for a session to disk
+pub fn flush_chat_session(project_id: &str, session_id: &str) -> Result<(), String> {
+    let entry = pending_chat_saves()
+        .lock()
+        .map_err(|_| "Chat save queue is poisoned".to_string())?
+        .remove(&pending_chat_key(project_id, session_id));
+
+    match entry {
+        Some(entry) => save_chat_session(&entry.session),
+        None => Ok(()),
+    }
+}
+
+/// Write every session with unsaved messages to disk (used on app exit)
+pub fn flush_all_chat_sessions() -> Result<(), String> {
+    let entries: Vec<PendingChatSave> = pending_chat_saves()
+        .lock()
+        .map_err(|_| "Chat save queue is poisoned".to_string())?
+        .drain()
+        .map(|(_, entry)| entry)
+        .collect();
+
+    for entry in entries {
+        save_chat_session(&entry.session)?;
+    }
+
+    Ok(())
+}
+
+// ============================================================================
+// Project Revisions
+// ============================================================================
+
+fn get_revisions_dir(project_id: &str) -> Result<PathBuf, String> {
+    let revisions_dir = get_project_dir(project_id)?.join(".revisions");
+
+    if !revisions_dir.exists() {
+        fs::create_dir_all(&revisions_dir).map_err(|e| e.to_string())?;
+    }
+
+    Ok(revisions_dir)
+}
+
+/// Stable FNV-1a hash used to store each distinct page content only once
+fn content_hash(content: &str) -> String {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for byte in content.as_bytes() {
+        hash ^= *byte as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    format!("{:016x}-{}", hash, content.len())
+}
+
+fn revision_dir_name(name: &str) -> Result<String, String> {
+    let dir_name = slug::slugify(name);
+    if dir_name.is_empty() {
+        return Err("Revision name must contain letters or numbers".to_string());
+    }
+    Ok(dir_name)
+}
+
+/// Capture all pages and metadata of a project as a named revision.
+/// Page contents are stored by hash, so pages unchanged since an earlier
+/// revision are not written again.
+pub fn snapshot_project(project_id: &str, name: &str) -> Result<ProjectRevision, String> {
+    let meta = load_project(project_id)?;
+    let revisions_dir = get_revisions_dir(project_id)?;
+
+    let revision_dir = revisions_dir.join(revision_dir_name(name)?);
+    if revision_dir.exists() {
+        return Err(format!("A revision named '{}' already exists", name));
+    }
+
+    let objects_dir = revisions_dir.join(".objects");
+    if !objects_dir.exists() {
+        fs::create_dir_all(&objects_dir).map_err(|e| e.to_string())?;
+    }
+
+    let mut pages = HashMap::new();
+    for page_name in &meta.page_order {
+        let content = load_page_content(project_id, page_name)?;
+        let hash = content_hash(&content);
+
+        let object_path = objects_dir.join(format!("{}.md", hash));
+        if !object_path.exists() {
+            write_atomic(&object_path, &content)
+                .map_err(|e| format!("Failed to write revision page: {}", e))?;
+        }
+
+        pages.insert(page_name.clone(), hash);
+    }
+
+    let manifest = RevisionManifest {
+        name: name.to_string(),
+        created_at: Utc::now(),
+        meta,
+        pages,
+    };
+
+    fs::create_dir_all(&revision_dir).map_err(|e| e.to_string())?;
+    let content = serde_json::to_string_pretty(&manifest)
+        .map_err(|e| format!("Failed to serialize revision: {}", e))?;
+    write_atomic(revision_dir.join("revision.json"), content)
+        .map_err(|e| format!("Failed to write revision: {}", e))?;
+
+    Ok(ProjectRevision::from(&manifest))
+}
+
+pub fn list_project_revisions(project_id: &str) -> Result<Vec<ProjectRevision>, String> {
+    let revisions_dir = get_revisions_dir(project_id)?;
+    let mut revisions = Vec::new();
+
+    if let Ok(entries) = fs::read_dir(&revisions_dir) {
+        for entry in entries.flatten() {
+            if entry.file_name().to_string_lossy().starts_with('.') {
+                continue;
+            }
+            let manifest_path = entry.path().join("revision.json");
+            if let Ok(content) = fs::read_to_string(&manifest_path) {
+                if let Ok(manifest) = serde_json::from_str::<RevisionManifest>(&content) {
+                    revisions.push(ProjectRevision::from(&manifest));
+                }
+            }
+        }
+    }
+
+    // Sort by created_at descending
+    revisions.sort_by_key(|r| std::cmp::Reverse(r.created_at));
+
+    Ok(revisions)
+}
+
+/// Replace the project's pages and metadata with those of a named revision
+pub fn restore_project_revision(project_id: &str, name: &str) -> Result<ProjectMeta, String> {
+    let revisions_dir = get_revisions_dir(project_id)?;
+    let manifest_path = revisions_dir.join(revision_dir_name(name)?).join("revision.json");
+
+    let content = fs::read_to_string(&manifest_path)
+        .map_err(|e| format!("Failed to read revision '{}': {}", name, e))?;
+    let manifest: RevisionManifest = serde_json::from_str(&content)
+        .map_err(|e| format!("Failed to parse revision: {}", e))?;
+
+    let pages_dir = get_project_dir(project_id)?.join("pages");
+    if !pages_dir.exists() {
+        fs::create_dir_all(&pages_dir).map_err(|e| e.to_string())?;
+    }
+
+    // Write back every page captured in the revision
+    let objects_dir = revisions_dir.join(".objects");
+    for (page_name, hash) in &manifest.pages {
+        let page_content = fs::read_to_string(objects_dir.join(format!("{}.md", hash)))
+            .map_err(|e| format!("Revision page '{}' is missing: {}", page_name, e))?;
+        write_atomic(pages_dir.join(page_name), page_content)
+            .map_err(|e| format!("Failed to write page: {}", e))?;
+    }
+
+    // Remove pages created after the revision was taken
+    if let Ok(entries) = fs::read_dir(&pages_dir) {
+        for entry in entries.flatten() {
+            let page_name = entry.file_name().to_string_lossy().to_string();
+            if entry.path().is_file() && !manifest.pages.contains_key(&page_name) {
+                fs::remove_file(entry.path()).map_err(|e| e.to_string())?;
+            }
+        }
+    }
+
+    let mut meta = manifest.meta;
+    meta.id = project_id.to_string();
+    meta.updated_at = Utc::now();
+    save_project(&meta)?;
+
+    Ok(meta)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn page_stem_transliterates_non_latin_titles() {
+        assert_eq!(page_stem(1, "Математика"), "01-matematika");
+        assert_eq!(page_stem(1, "Μαθηματικά"), "01-mathematika");
+        assert_eq!(page_stem(1, "数学入門"), "01-shu-xue-ru-men");
+    }
+
+    #[test]
+    fn page_stem_falls_back_to_a_unique_stem() {
+        // Private-use characters transliterate to nothing
+        let first = page_stem(3, "\u{E000}\u{E001}");
+        let second = page_stem(3, "\u{E000}\u{E001}");
+        assert!(first.starts_with("03-page-"));
+        assert_eq!(first.len(), "03-page-".len() + 8);
+        assert_ne!(first, second);
+    }
+
+    #[test]
+    fn pages_with_the_same_title_get_distinct_files() {
+        let project = TestProject { id: create_new_project("Test", "").unwrap().id };
+        let first = add_page_to_project(&project.id, "数学", "# 数学\n").unwrap();
+        let second = add_page_to_project(&project.id, "数学", "# 数学\n").unwrap();
+
+        assert_ne!(first, second);
+        let pages_dir = get_project_dir(&project.id).unwrap().join("pages");
+        assert!(pages_dir.join(&first).is_file());
+        assert!(pages_dir.join(&second).is_file());
+        assert_eq!(load_project(&project.id).unwrap().page_order, vec![first.clone(), second]);
+
+        // Same position and title, so only the collision suffix keeps it apart
+        let third = insert_page_at(&project.id, "数学", "# 数学\n", 0).unwrap();
+        assert_eq!(third, first.replace(".md", "-2.md"));
+        assert!(pages_dir.join(&first).is_file());
+    }
+
+    #[test]
+    fn remove_expansion_strips_a_multi_line_block() {
+        let project = TestProject::new();
+        let original = "# Title\n\nIntro.\n\nOutro.\n";
+        let expanded = "# Title\n\nIntro.\n\n<details class=\"ai-expansion\">\nMore detail.\n</details>\n\nOutro.\n";
+        save_page_content(&project.id, "01-page.md", original).unwrap();
+        save_page_content(&project.id, "01-page.md", expanded).unwrap();
+        record_expansion(&project.id, "01-page.md", "exp_1", original, expanded).unwrap();
+
+        let updated = remove_expansion(&project.id, "01-page.md", "exp_1").unwrap();
+        assert_eq!(updated, original);
+    }
+
+    #[test]
+    fn write_atomic_removes_the_temp_file_on_error() {
+        // Renaming over a non-empty directory fails after the temp file is written
+        let dir = std::env::temp_dir().join(format!("liminal-test-{}", Uuid::new_v4()));
+        let target = dir.join("meta.json");
+        fs::create_dir_all(target.join("blocker")).unwrap();
+
+        let result = write_atomic(&target, "{}");
+        let tmp_exists = dir.join("meta.json.tmp").exists();
+        let _ = fs::remove_dir_all(&dir);
+
+        assert!(result.is_err());
+        assert!(!tmp_exists);
+    }
+}
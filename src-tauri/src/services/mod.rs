@@ -3,3 +3,4 @@ pub mod config_service;
 pub mod ai_service;
 pub mod llm_client;
 pub mod pdf_service;
+pub mod backup_service;
@@ -1,37 +1,208 @@
-use pulldown_cmark::{Parser, Event, Tag, TagEnd, CodeBlockKind};
+use pulldown_cmark::{Parser, Options, Event, Tag, TagEnd, CodeBlockKind, Alignment, HeadingLevel};
 use headless_chrome::{Browser, LaunchOptions, types::PrintToPdfOptions};
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use serde::{Deserialize, Serialize};
+use crate::models::Annotation;
+use crate::services::file_service;
+use std::collections::HashMap;
 use std::fs;
+use std::path::{Path, PathBuf};
 use std::time::Duration;
+use uuid::Uuid;
 
 const WEBSITE_URL: &str = "https://liminal.wrappt.tech";
 const WATERMARK_TEXT: &str = "Customize your learning with Liminal";
+const TEMP_HTML_PREFIX: &str = "liminal_export";
+const STALE_TEMP_AGE: Duration = Duration::from_secs(10 * 60);
+
+// Fonts and syntax highlighting are compiled in so exports work offline.
+// Fetched by scripts/fetch-pdf-assets.sh; build.rs fails if they're missing.
+const HIGHLIGHT_JS: &str = include_str!("../../assets/pdf/highlight.min.js");
+const HIGHLIGHT_CSS: &str = include_str!("../../assets/pdf/github-dark.min.css");
+const FONT_REGULAR: &[u8] = include_bytes!("../../assets/pdf/libre-baskerville-400.woff2");
+const FONT_ITALIC: &[u8] = include_bytes!("../../assets/pdf/libre-baskerville-400-italic.woff2");
+const FONT_BOLD: &[u8] = include_bytes!("../../assets/pdf/libre-baskerville-700.woff2");
+const KATEX_JS: &str = include_str!("../../assets/pdf/katex/katex.min.js");
+const KATEX_CSS: &str = include_str!("../../assets/pdf/katex/katex.min.css");
+
+macro_rules! katex_fonts {
+    ($($name:literal),* $(,)?) => {
+        &[$(($name, include_bytes!(concat!("../../assets/pdf/katex/fonts/", $name, ".woff2")))),*]
+    };
+}
+
+/// KaTeX's woff2 fonts, swapped into its stylesheet as data URIs
+const KATEX_FONTS: &[(&str, &[u8])] = katex_fonts!(
+    "KaTeX_AMS-Regular",
+    "KaTeX_Caligraphic-Bold",
+    "KaTeX_Caligraphic-Regular",
+    "KaTeX_Fraktur-Bold",
+    "KaTeX_Fraktur-Regular",
+    "KaTeX_Main-Bold",
+    "KaTeX_Main-BoldItalic",
+    "KaTeX_Main-Italic",
+    "KaTeX_Main-Regular",
+    "KaTeX_Math-BoldItalic",
+    "KaTeX_Math-Italic",
+    "KaTeX_SansSerif-Bold",
+    "KaTeX_SansSerif-Italic",
+    "KaTeX_SansSerif-Regular",
+    "KaTeX_Script-Regular",
+    "KaTeX_Size1-Regular",
+    "KaTeX_Size2-Regular",
+    "KaTeX_Size3-Regular",
+    "KaTeX_Size4-Regular",
+    "KaTeX_Typewriter-Regular",
+);
+
+/// Paper sizes offered for PDF export
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PaperSize {
+    #[default]
+    A4,
+    A5,
+    Letter,
+    Legal,
+}
+
+impl PaperSize {
+    /// Portrait width and height in inches
+    fn dimensions(self) -> (f64, f64) {
+        match self {
+            PaperSize::A4 => (8.27, 11.69),
+            PaperSize::A5 => (5.83, 8.27),
+            PaperSize::Letter => (8.5, 11.0),
+            PaperSize::Legal => (8.5, 14.0),
+        }
+    }
+
+    /// Name for the CSS `@page { size }` property
+    fn css_name(self) -> &'static str {
+        match self {
+            PaperSize::A4 => "A4",
+            PaperSize::A5 => "A5",
+            PaperSize::Letter => "letter",
+            PaperSize::Legal => "legal",
+        }
+    }
+}
+
+/// Page margins in inches
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PdfMargins {
+    pub top: f64,
+    pub bottom: f64,
+    pub left: f64,
+    pub right: f64,
+}
+
+/// Every adjustable knob of a PDF export. Missing fields take the defaults,
+/// which match what exports have always looked like.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct PdfOptions {
+    pub paper_size: PaperSize,
+    pub landscape: bool,
+    /// Margins to use instead of the defaults (which leave room for the
+    /// header and footer when page numbers are shown)
+    pub margins: Option<PdfMargins>,
+    /// Add the Liminal credit to every page
+    pub watermark: bool,
+    /// Include the website link in the watermark
+    pub watermark_link: bool,
+    /// CSS font stack for body text instead of the bundled Libre Baskerville
+    pub font_family: Option<String>,
+    /// Every page gets a "page / total" footer and a header with the book title
+    pub show_page_numbers: bool,
+    /// Keep the rendered HTML next to the PDF (same basename, `.html`)
+    /// for diagnosing styling issues
+    pub debug_keep_html: bool,
+    /// Print the reader's annotations as notes at the end of each chapter
+    pub include_annotations: bool,
+}
+
+impl Default for PdfOptions {
+    fn default() -> Self {
+        Self {
+            paper_size: PaperSize::default(),
+            landscape: false,
+            margins: None,
+            watermark: true,
+            watermark_link: true,
+            font_family: None,
+            show_page_numbers: false,
+            debug_keep_html: false,
+            include_annotations: false,
+        }
+    }
+}
+
+impl PdfOptions {
+    fn margins(&self) -> PdfMargins {
+        self.margins.unwrap_or_else(|| {
+            // Chrome draws the header and footer inside the page margins and
+            // ignores page CSS for them, so make room when they're shown
+            let (top, bottom) = if self.show_page_numbers { (0.7, 0.8) } else { (0.4, 0.6) };
+            PdfMargins { top, bottom, left: 0.4, right: 0.4 }
+        })
+    }
+}
+
+/// Removes the temporary export HTML when dropped, including on early returns.
+/// A guard with `keep` set leaves the file in place.
+struct TempFileGuard {
+    path: PathBuf,
+    keep: bool,
+}
+
+impl Drop for TempFileGuard {
+    fn drop(&mut self) {
+        if !self.keep {
+            let _ = fs::remove_file(&self.path);
+        }
+    }
+}
+
+/// Write export HTML to a file of its own in the temp dir, so concurrent
+/// exports don't clobber each other. The file is removed with the guard.
+fn write_temp_html(html: &str) -> Result<TempFileGuard, String> {
+    let path = std::env::temp_dir().join(format!("{}_{}.html", TEMP_HTML_PREFIX, Uuid::new_v4()));
+    fs::write(&path, html)
+        .map_err(|e| format!("Failed to write temporary HTML: {}", e))?;
+    Ok(TempFileGuard { path, keep: false })
+}
 
-/// Generate a PDF document with embedded CSS that matches the app's styling
+/// Generate a PDF document with embedded CSS that matches the app's styling,
+/// laid out according to `options`. A `cover_image` (file path or data URI)
+/// replaces the text title page. `annotations` holds each page's notes, in
+/// page order, and is only used with `options.include_annotations`.
 pub fn export_project_to_pdf(
     title: &str,
     pages: Vec<String>,
+    annotations: &[Vec<Annotation>],
     output_path: &str,
+    options: &PdfOptions,
+    cover_image: Option<&str>,
 ) -> Result<(), String> {
-    let mut html_content = String::new();
-
-    // Process each page's markdown to HTML
-    for (idx, markdown) in pages.iter().enumerate() {
-        if idx > 0 {
-            html_content.push_str(r#"<div class="page-break"></div>"#);
-        }
-        let page_html = markdown_to_html(markdown);
-        html_content.push_str(&format!(r#"<section class="chapter">{}</section>"#, page_html));
-    }
-
-    let full_html = generate_full_html(title, &html_content);
+    let debug_keep_html = options.debug_keep_html;
+    let show_page_numbers = options.show_page_numbers;
+    let annotations = if options.include_annotations { annotations } else { &[] };
+    let full_html = render_book_html(title, &pages, annotations, options, cover_image)?;
 
     // Write HTML to a temporary file (data URLs have size limits)
-    let temp_dir = std::env::temp_dir();
-    let temp_html_path = temp_dir.join("liminal_export.html");
-    fs::write(&temp_html_path, &full_html)
-        .map_err(|e| format!("Failed to write temporary HTML: {}", e))?;
+    let temp_html = if debug_keep_html {
+        let path = Path::new(output_path).with_extension("html");
+        fs::write(&path, &full_html)
+            .map_err(|e| format!("Failed to write temporary HTML: {}", e))?;
+        eprintln!("Keeping export HTML for debugging at {}", path.display());
+        TempFileGuard { path, keep: true }
+    } else {
+        write_temp_html(&full_html)?
+    };
 
-    let file_url = format!("file://{}", temp_html_path.to_string_lossy());
+    let file_url = format!("file://{}", temp_html.path.to_string_lossy());
 
     // Use headless Chrome to generate PDF
     let browser = Browser::new(
@@ -52,25 +223,30 @@ pub fn export_project_to_pdf(
     tab.wait_until_navigated()
         .map_err(|e| format!("Failed to wait for navigation: {}", e))?;
 
-    // Wait a bit for fonts and highlight.js to load
-    std::thread::sleep(Duration::from_millis(1500));
+    // Everything is inline and highlighting/math typeset synchronously while
+    // the page loads, so only font decoding needs waiting on
+    tab.evaluate("document.fonts.ready.then(() => true)", true)
+        .map_err(|e| format!("Failed to wait for fonts: {}", e))?;
+
+    let (paper_width, paper_height) = options.paper_size.dimensions();
+    let margins = options.margins();
 
     // Generate PDF with options
     let pdf_options = PrintToPdfOptions {
-        landscape: Some(false),
-        display_header_footer: Some(false),
+        landscape: Some(options.landscape),
+        display_header_footer: Some(show_page_numbers),
         print_background: Some(true),
         scale: Some(1.0),
-        paper_width: Some(8.27),  // A4 width in inches
-        paper_height: Some(11.69), // A4 height in inches
-        margin_top: Some(0.4),
-        margin_bottom: Some(0.6),
-        margin_left: Some(0.4),
-        margin_right: Some(0.4),
+        paper_width: Some(paper_width),
+        paper_height: Some(paper_height),
+        margin_top: Some(margins.top),
+        margin_bottom: Some(margins.bottom),
+        margin_left: Some(margins.left),
+        margin_right: Some(margins.right),
         page_ranges: None,
         ignore_invalid_page_ranges: None,
-        header_template: None,
-        footer_template: None,
+        header_template: show_page_numbers.then(|| header_template(title)),
+        footer_template: show_page_numbers.then(footer_template),
         prefer_css_page_size: Some(true),
         transfer_mode: None,
         generate_tagged_pdf: None,
@@ -81,7 +257,7 @@ pub fn export_project_to_pdf(
         .map_err(|e| format!("Failed to generate PDF: {}", e))?;
 
     // Clean up temporary file
-    let _ = fs::remove_file(&temp_html_path);
+    drop(temp_html);
 
     fs::write(output_path, pdf_data)
         .map_err(|e| format!("Failed to write PDF: {}", e))?;
@@ -89,19 +265,177 @@ pub fn export_project_to_pdf(
     Ok(())
 }
 
-fn markdown_to_html(markdown: &str) -> String {
-    let parser = Parser::new(markdown);
+/// Write the book as a single self-contained HTML file. Styles, fonts and
+/// highlight.js are inlined, so it opens anywhere without network access
+/// and needs no Chrome to produce.
+pub fn export_project_to_html(
+    title: &str,
+    pages: Vec<String>,
+    output_path: &str,
+    cover_image: Option<&str>,
+) -> Result<(), String> {
+    let full_html = render_book_html(title, &pages, &[], &PdfOptions::default(), cover_image)?;
+    fs::write(output_path, full_html)
+        .map_err(|e| format!("Failed to write HTML: {}", e))
+}
+
+/// Render every page into the full export document
+fn render_book_html(
+    title: &str,
+    pages: &[String],
+    annotations: &[Vec<Annotation>],
+    options: &PdfOptions,
+    cover_image: Option<&str>,
+) -> Result<String, String> {
+    let mut html_content = String::new();
+
+    // Process each page's markdown to HTML
+    for (idx, markdown) in pages.iter().enumerate() {
+        if idx > 0 {
+            html_content.push_str(r#"<div class="page-break"></div>"#);
+        }
+        let mut page_html = markdown_to_html(markdown, idx + 1);
+        if let Some(notes) = annotations.get(idx).filter(|notes| !notes.is_empty()) {
+            page_html.push_str(&annotations_html(notes));
+        }
+        html_content.push_str(&format!(r#"<section class="chapter">{}</section>"#, page_html));
+    }
+
+    let cover = cover_image.map(cover_image_url).transpose()?;
+
+    Ok(generate_full_html(title, &html_content, options, cover.as_deref()))
+}
+
+/// A chapter's annotations as a notes list, each labelled with its line
+fn annotations_html(notes: &[Annotation]) -> String {
+    let items: String = notes.iter()
+        .map(|note| format!(
+            r#"<li><span class="annotation-line">Line {}</span> {}</li>"#,
+            note.line,
+            html_escape(&note.text)
+        ))
+        .collect();
+    format!(r#"<aside class="annotations"><h4>Notes</h4><ul>{}</ul></aside>"#, items)
+}
+
+/// The cover as a URL the export can embed: data URIs pass through, image
+/// files are inlined so the HTML export stays self-contained
+fn cover_image_url(cover: &str) -> Result<String, String> {
+    if cover.starts_with("data:") {
+        return Ok(cover.to_string());
+    }
+
+    let path = Path::new(cover);
+    let mime = match path.extension().and_then(|e| e.to_str()).map(|e| e.to_ascii_lowercase()).as_deref() {
+        Some("png") => "image/png",
+        Some("jpg") | Some("jpeg") => "image/jpeg",
+        Some("gif") => "image/gif",
+        Some("webp") => "image/webp",
+        Some("svg") => "image/svg+xml",
+        _ => return Err(format!("Unsupported cover image type: {}", cover)),
+    };
+    let data = fs::read(path)
+        .map_err(|e| format!("Failed to read cover image: {}", e))?;
+
+    Ok(format!("data:{};base64,{}", mime, BASE64.encode(data)))
+}
+
+/// KaTeX's stylesheet with its fonts inlined as data URIs. The woff/ttf
+/// fallbacks it also lists are never reached since Chrome takes woff2.
+fn get_katex_css() -> String {
+    KATEX_FONTS.iter().fold(KATEX_CSS.to_string(), |css, (name, data)| {
+        css.replace(
+            &format!("url(fonts/{}.woff2)", name),
+            &format!("url(data:font/woff2;base64,{})", BASE64.encode(data)),
+        )
+    })
+}
+
+/// Running header with the book title. Chrome renders header/footer templates
+/// in isolation (no page CSS, default font size of zero), so style inline.
+fn header_template(title: &str) -> String {
+    format!(
+        r#"<div style="width: 100%; padding: 0 1.5cm; font-family: Georgia, serif; font-size: 8pt; font-style: italic; color: #8a7f6e; text-align: center;">{}</div>"#,
+        html_escape(title)
+    )
+}
+
+/// Footer with Chrome's page counters
+fn footer_template() -> String {
+    r#"<div style="width: 100%; padding: 0 1.5cm; font-family: Georgia, serif; font-size: 8pt; color: #8a7f6e; text-align: center;"><span class="pageNumber"></span> / <span class="totalPages"></span></div>"#.to_string()
+}
+
+/// Remove temporary export HTML files left behind by interrupted exports.
+/// Files younger than a few minutes are skipped since an export may still be using them.
+/// Returns the number of files removed.
+pub fn cleanup_temp_exports() -> Result<usize, String> {
+    let entries = fs::read_dir(std::env::temp_dir())
+        .map_err(|e| format!("Failed to read temp directory: {}", e))?;
+
+    let mut removed = 0;
+    for entry in entries.flatten() {
+        let name = entry.file_name().to_string_lossy().to_string();
+        if !name.starts_with(TEMP_HTML_PREFIX) || !name.ends_with(".html") {
+            continue;
+        }
+
+        let is_stale = entry.metadata()
+            .and_then(|m| m.modified())
+            .ok()
+            .and_then(|modified| modified.elapsed().ok())
+            .is_none_or(|age| age >= STALE_TEMP_AGE);
+
+        if is_stale && fs::remove_file(entry.path()).is_ok() {
+            removed += 1;
+        }
+    }
+
+    Ok(removed)
+}
+
+/// Render one chapter's markdown. `chapter` keeps footnote anchors unique
+/// across the whole book.
+fn markdown_to_html(markdown: &str, chapter: usize) -> String {
+    let options = Options::ENABLE_TABLES
+        | Options::ENABLE_TASKLISTS
+        | Options::ENABLE_STRIKETHROUGH
+        | Options::ENABLE_MATH
+        | Options::ENABLE_FOOTNOTES
+        | Options::ENABLE_DEFINITION_LIST;
+    let parser = Parser::new_ext(markdown, options);
     let mut html = String::new();
     let mut in_code_block = false;
     let mut code_lang = String::new();
     let mut code_content = String::new();
+    let mut table_alignments: Vec<Alignment> = Vec::new();
+    let mut in_table_head = false;
+    let mut table_cell_index = 0;
+    // Closing tag for each open list, innermost last
+    let mut list_stack: Vec<&str> = Vec::new();
+    // Footnotes are numbered by first appearance and collected at the end
+    // of the chapter; `html` is parked while a definition is being rendered
+    let mut footnote_numbers: HashMap<String, usize> = HashMap::new();
+    let mut footnotes: Vec<(usize, String)> = Vec::new();
+    let mut parked_html: Option<(usize, String)> = None;
+    // Chapter titles get an id so cross-reference links (`#anchor`) resolve;
+    // the title text is collected until its heading closes
+    let mut title_text: Option<(usize, String)> = None;
 
     for event in parser {
         match event {
             Event::Start(Tag::Heading { level, .. }) => {
                 html.push_str(&format!("<h{}>", level as u8));
+                if level == HeadingLevel::H1 {
+                    title_text = Some((html.len() - 1, String::new()));
+                }
             }
             Event::End(TagEnd::Heading(level)) => {
+                if let Some((at, text)) = title_text.take() {
+                    let anchor = file_service::chapter_anchor(&text);
+                    if !anchor.is_empty() {
+                        html.insert_str(at, &format!(r#" id="{}""#, html_escape(&anchor)));
+                    }
+                }
                 html.push_str(&format!("</h{}>", level as u8));
             }
             Event::Start(Tag::Paragraph) => {
@@ -120,28 +454,33 @@ fn markdown_to_html(markdown: &str) -> String {
             }
             Event::End(TagEnd::CodeBlock) => {
                 in_code_block = false;
-                let lang_class = if code_lang.is_empty() {
-                    "plaintext".to_string()
+                // Without a fence language, leave the class off so
+                // highlight.js auto-detects it
+                let lang = code_lang.split_whitespace().next().unwrap_or("");
+                if lang.is_empty() {
+                    html.push_str(&format!("<pre><code>{}</code></pre>", html_escape(&code_content)));
                 } else {
-                    code_lang.clone()
-                };
-                html.push_str(&format!(
-                    r#"<pre><code class="language-{}">{}</code></pre>"#,
-                    lang_class,
-                    html_escape(&code_content)
-                ));
+                    html.push_str(&format!(
+                        r#"<pre><code class="language-{}">{}</code></pre>"#,
+                        html_escape(lang),
+                        html_escape(&code_content)
+                    ));
+                }
             }
             Event::Start(Tag::List(None)) => {
+                list_stack.push("</ul>");
                 html.push_str("<ul>");
             }
-            Event::End(TagEnd::List(false)) => {
-                html.push_str("</ul>");
-            }
-            Event::Start(Tag::List(Some(_))) => {
-                html.push_str("<ol>");
+            Event::Start(Tag::List(Some(start))) => {
+                list_stack.push("</ol>");
+                if start == 1 {
+                    html.push_str("<ol>");
+                } else {
+                    html.push_str(&format!(r#"<ol start="{}">"#, start));
+                }
             }
-            Event::End(TagEnd::List(true)) => {
-                html.push_str("</ol>");
+            Event::End(TagEnd::List(_)) => {
+                html.push_str(list_stack.pop().unwrap_or("</ul>"));
             }
             Event::Start(Tag::Item) => {
                 html.push_str("<li>");
@@ -155,6 +494,24 @@ fn markdown_to_html(markdown: &str) -> String {
             Event::End(TagEnd::BlockQuote(_)) => {
                 html.push_str("</blockquote>");
             }
+            Event::Start(Tag::DefinitionList) => {
+                html.push_str("<dl>");
+            }
+            Event::End(TagEnd::DefinitionList) => {
+                html.push_str("</dl>");
+            }
+            Event::Start(Tag::DefinitionListTitle) => {
+                html.push_str("<dt>");
+            }
+            Event::End(TagEnd::DefinitionListTitle) => {
+                html.push_str("</dt>");
+            }
+            Event::Start(Tag::DefinitionListDefinition) => {
+                html.push_str("<dd>");
+            }
+            Event::End(TagEnd::DefinitionListDefinition) => {
+                html.push_str("</dd>");
+            }
             Event::Start(Tag::Strong) => {
                 html.push_str("<strong>");
             }
@@ -167,10 +524,86 @@ fn markdown_to_html(markdown: &str) -> String {
             Event::End(TagEnd::Emphasis) => {
                 html.push_str("</em>");
             }
+            Event::Start(Tag::Table(alignments)) => {
+                table_alignments = alignments;
+                html.push_str("<table>");
+            }
+            Event::End(TagEnd::Table) => {
+                html.push_str("</tbody></table>");
+            }
+            Event::Start(Tag::TableHead) => {
+                in_table_head = true;
+                table_cell_index = 0;
+                html.push_str("<thead><tr>");
+            }
+            Event::End(TagEnd::TableHead) => {
+                in_table_head = false;
+                html.push_str("</tr></thead><tbody>");
+            }
+            Event::Start(Tag::TableRow) => {
+                table_cell_index = 0;
+                html.push_str("<tr>");
+            }
+            Event::End(TagEnd::TableRow) => {
+                html.push_str("</tr>");
+            }
+            Event::Start(Tag::TableCell) => {
+                let cell = if in_table_head { "th" } else { "td" };
+                let align = match table_alignments.get(table_cell_index) {
+                    Some(Alignment::Left) => r#" style="text-align: left""#,
+                    Some(Alignment::Center) => r#" style="text-align: center""#,
+                    Some(Alignment::Right) => r#" style="text-align: right""#,
+                    _ => "",
+                };
+                html.push_str(&format!("<{}{}>", cell, align));
+            }
+            Event::End(TagEnd::TableCell) => {
+                html.push_str(if in_table_head { "</th>" } else { "</td>" });
+                table_cell_index += 1;
+            }
+            Event::Start(Tag::Link { dest_url, title, .. }) => {
+                if title.is_empty() {
+                    html.push_str(&format!(r#"<a href="{}">"#, html_escape(&dest_url)));
+                } else {
+                    html.push_str(&format!(
+                        r#"<a href="{}" title="{}">"#,
+                        html_escape(&dest_url),
+                        html_escape(&title)
+                    ));
+                }
+            }
+            Event::End(TagEnd::Link) => {
+                html.push_str("</a>");
+            }
+            Event::Start(Tag::Strikethrough) => {
+                html.push_str("<del>");
+            }
+            Event::End(TagEnd::Strikethrough) => {
+                html.push_str("</del>");
+            }
+            Event::TaskListMarker(checked) => {
+                if checked {
+                    html.push_str(r#"<input type="checkbox" class="task-checkbox" checked disabled> "#);
+                } else {
+                    html.push_str(r#"<input type="checkbox" class="task-checkbox" disabled> "#);
+                }
+            }
+            Event::InlineMath(tex) => {
+                html.push_str(&format!(r#"<span class="math math-inline">{}</span>"#, html_escape(&tex)));
+            }
+            Event::DisplayMath(tex) => {
+                html.push_str(&format!(r#"<span class="math math-display">{}</span>"#, html_escape(&tex)));
+            }
             Event::Code(text) => {
+                if let Some((_, title)) = title_text.as_mut() {
+                    title.push_str(&text);
+                }
                 html.push_str(&format!("<code>{}</code>", html_escape(&text)));
             }
             Event::Text(text) => {
+                if let Some((_, title)) = title_text.as_mut() {
+                    title.push_str(&text);
+                }
                 if in_code_block {
                     code_content.push_str(&text);
                 } else {
@@ -190,10 +623,48 @@ fn markdown_to_html(markdown: &str) -> String {
             Event::Rule => {
                 html.push_str("<hr>");
             }
+            Event::FootnoteReference(label) => {
+                let next = footnote_numbers.len() + 1;
+                let first_reference = !footnote_numbers.contains_key(label.as_ref());
+                let number = *footnote_numbers.entry(label.to_string()).or_insert(next);
+                let id = if first_reference {
+                    format!(r#" id="fnref-{}-{}""#, chapter, number)
+                } else {
+                    String::new()
+                };
+                html.push_str(&format!(
+                    r##"<sup class="footnote-ref"{}><a href="#fn-{}-{}">{}</a></sup>"##,
+                    id, chapter, number, number
+                ));
+            }
+            Event::Start(Tag::FootnoteDefinition(label)) => {
+                let next = footnote_numbers.len() + 1;
+                let number = *footnote_numbers.entry(label.to_string()).or_insert(next);
+                parked_html = Some((number, std::mem::take(&mut html)));
+            }
+            Event::End(TagEnd::FootnoteDefinition) => {
+                if let Some((number, chapter_html)) = parked_html.take() {
+                    footnotes.push((number, std::mem::replace(&mut html, chapter_html)));
+                }
+            }
             _ => {}
         }
     }
 
+    if !footnotes.is_empty() {
+        footnotes.sort_by_key(|(number, _)| *number);
+        html.push_str(r#"<section class="footnotes"><ol>"#);
+        for (number, body) in footnotes {
+            html.push_str(&format!(
+                r##"<li id="fn-{ch}-{n}" value="{n}">{body} <a href="#fnref-{ch}-{n}" class="footnote-backref">&#8617;</a></li>"##,
+                ch = chapter,
+                n = number,
+                body = body
+            ));
+        }
+        html.push_str("</ol></section>");
+    }
+
     html
 }
 
@@ -204,52 +675,130 @@ fn html_escape(text: &str) -> String {
         .replace('"', "&quot;")
 }
 
-fn generate_full_html(title: &str, content: &str) -> String {
+fn generate_full_html(title: &str, content: &str, options: &PdfOptions, cover_url: Option<&str>) -> String {
+    let watermark = if options.watermark {
+        let link = if options.watermark_link {
+            format!(r#"
+        <a href="{url}" class="watermark-url">{url}</a>"#, url = WEBSITE_URL)
+        } else {
+            String::new()
+        };
+        format!(
+            r#"<div class="watermark">
+        <div class="watermark-text">{text}</div>{link}
+    </div>"#,
+            text = WATERMARK_TEXT,
+            link = link,
+        )
+    } else {
+        String::new()
+    };
+
+    // Page size comes from CSS (Chrome is told to prefer it), so it follows the options
+    let mut css = format!(
+        "{}\n@page {{ size: {} {}; }}\n",
+        get_pdf_css(),
+        options.paper_size.css_name(),
+        if options.landscape { "landscape" } else { "portrait" }
+    );
+
+    // Characters that could end the declaration or the style block are dropped
+    if let Some(family) = options.font_family.as_deref().map(str::trim).filter(|f| !f.is_empty()) {
+        let family: String = family.chars().filter(|c| !matches!(c, ';' | '{' | '}' | '<' | '>')).collect();
+        css.push_str(&format!(":root {{ --font-serif: {}; }}\n", family));
+    }
+
+    let title_page = match cover_url {
+        Some(url) => format!(
+            r#"<header class="title-page cover-page" role="img" aria-label="{title}" style="background-image: url('{url}')"></header>"#,
+            title = html_escape(title),
+            url = url.replace('\'', "%27"),
+        ),
+        None => format!(
+            r#"<header class="title-page">
+            <h1 class="book-title">{title}</h1>
+            <p class="book-subtitle">Generated with Liminal</p>
+        </header>"#,
+            title = html_escape(title),
+        ),
+    };
+
     format!(r##"<!DOCTYPE html>
 <html lang="en">
 <head>
     <meta charset="UTF-8">
     <meta name="viewport" content="width=device-width, initial-scale=1.0">
     <title>{title} - Liminal</title>
-    <link rel="preconnect" href="https://fonts.googleapis.com">
-    <link rel="preconnect" href="https://fonts.gstatic.com" crossorigin>
-    <link href="https://fonts.googleapis.com/css2?family=Libre+Baskerville:ital,wght@0,400;0,700;1,400&display=swap" rel="stylesheet">
-    <link rel="stylesheet" href="https://cdnjs.cloudflare.com/ajax/libs/highlight.js/11.9.0/styles/github-dark.min.css">
-    <script src="https://cdnjs.cloudflare.com/ajax/libs/highlight.js/11.9.0/highlight.min.js"></script>
+    <style>
+        {fonts}
+    </style>
+    <style>
+        {highlight_css}
+    </style>
+    <script>
+        {highlight_js}
+    </script>
+    <style>
+        {katex_css}
+    </style>
+    <script>
+        {katex_js}
+    </script>
     <style>
         {css}
     </style>
 </head>
 <body>
     <div class="document">
-        <header class="title-page">
-            <h1 class="book-title">{title}</h1>
-            <p class="book-subtitle">Generated with Liminal</p>
-        </header>
+        {title_page}
 
         <main class="content">
             {content}
         </main>
     </div>
 
-    <div class="watermark">
-        <div class="watermark-text">{watermark}</div>
-        <a href="{url}" class="watermark-url">{url}</a>
-    </div>
+    {watermark}
 
     <script>
         hljs.highlightAll();
+        document.querySelectorAll('.math').forEach((el) => {{
+            katex.render(el.textContent, el, {{
+                displayMode: el.classList.contains('math-display'),
+                throwOnError: false
+            }});
+        }});
     </script>
 </body>
 </html>"##,
         title = html_escape(title),
+        title_page = title_page,
         content = content,
-        watermark = WATERMARK_TEXT,
-        url = WEBSITE_URL,
-        css = get_pdf_css()
+        watermark = watermark,
+        fonts = get_font_faces(),
+        highlight_css = HIGHLIGHT_CSS,
+        highlight_js = HIGHLIGHT_JS,
+        katex_css = get_katex_css(),
+        katex_js = KATEX_JS,
+        css = css
     )
 }
 
+/// `@font-face` rules for the bundled Libre Baskerville, inlined as data URIs
+fn get_font_faces() -> String {
+    [(FONT_REGULAR, "normal", 400), (FONT_ITALIC, "italic", 400), (FONT_BOLD, "normal", 700)]
+        .iter()
+        .map(|(data, style, weight)| {
+            format!(
+                "@font-face {{ font-family: 'Libre Baskerville'; font-style: {}; font-weight: {}; src: url(data:font/woff2;base64,{}) format('woff2'); }}",
+                style,
+                weight,
+                BASE64.encode(data)
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
 fn get_pdf_css() -> &'static str {
     r##"
 /* Reset */
@@ -261,7 +810,6 @@ fn get_pdf_css() -> &'static str {
 
 /* Page setup for printing */
 @page {
-    size: A4;
     margin: 2cm 1.5cm 2.5cm 1.5cm;
 }
 
@@ -351,6 +899,15 @@ body {
     color: var(--color-text-secondary);
 }
 
+/* Cover image title page, bleeding out over the document padding */
+.cover-page {
+    margin: 0 -1cm;
+    padding: 0;
+    background-size: cover;
+    background-position: center;
+    background-repeat: no-repeat;
+}
+
 /* Content area */
 .content {
     padding: 0;
@@ -415,6 +972,29 @@ li::marker {
     color: var(--color-text-secondary);
 }
 
+/* Task lists */
+li:has(> .task-checkbox) {
+    list-style: none;
+    margin-left: -1.4em;
+}
+
+.task-checkbox {
+    width: 0.9em;
+    height: 0.9em;
+    margin-right: 0.4em;
+    vertical-align: -0.05em;
+    accent-color: var(--color-accent);
+}
+
+li:has(> .task-checkbox:checked) {
+    color: var(--color-text-tertiary);
+}
+
+del {
+    text-decoration: line-through;
+    color: var(--color-text-tertiary);
+}
+
 /* Blockquotes */
 blockquote {
     margin: 1.2em 0;
@@ -502,6 +1082,107 @@ a {
     text-underline-offset: 2px;
 }
 
+/* Footnotes */
+.footnote-ref {
+    font-size: 0.7em;
+    line-height: 0;
+}
+
+.footnote-ref a {
+    text-decoration: none;
+}
+
+.footnotes {
+    margin-top: 2.5em;
+    padding-top: 0.8em;
+    border-top: 1px solid var(--color-text-tertiary);
+    font-size: 0.85em;
+}
+
+.footnotes li p {
+    display: inline;
+    text-indent: 0;
+}
+
+.footnote-backref {
+    text-decoration: none;
+}
+
+/* Definition lists (glossary) */
+dt {
+    font-weight: 600;
+    margin-top: 0.8em;
+    page-break-after: avoid;
+}
+
+dd {
+    margin: 0.2em 0 0 1.5em;
+}
+
+/* Reader annotations */
+.annotations {
+    margin-top: 2em;
+    padding: 0.8em 1em;
+    border-left: 3px solid var(--color-accent);
+    background: var(--color-bg-elevated);
+    font-size: 0.85em;
+    page-break-inside: avoid;
+}
+
+.annotations h4 {
+    margin-top: 0;
+}
+
+.annotations ul {
+    list-style: none;
+    padding-left: 0;
+}
+
+.annotation-line {
+    font-variant: small-caps;
+    color: var(--color-text-tertiary);
+    margin-right: 0.4em;
+}
+
+/* Math */
+.math-display {
+    display: block;
+    margin: 1em 0;
+    text-align: center;
+    overflow-x: auto;
+    page-break-inside: avoid;
+}
+
+.katex {
+    font-size: 1.1em;
+}
+
+/* Tables */
+table {
+    width: 100%;
+    border-collapse: collapse;
+    margin: 1.2em 0;
+    font-size: 0.9rem;
+    line-height: 1.5;
+    page-break-inside: avoid;
+}
+
+th, td {
+    border: 1px solid var(--color-border);
+    padding: 0.45em 0.7em;
+    text-align: left;
+    vertical-align: top;
+}
+
+th {
+    font-weight: 700;
+    background: rgba(44, 36, 22, 0.06);
+}
+
+tbody tr:nth-child(even) {
+    background: rgba(44, 36, 22, 0.03);
+}
+
 /* Watermark - fixed position for every page */
 .watermark {
     position: fixed;
@@ -539,3 +1220,74 @@ a {
 }
 "##
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn temp_html_files_are_separate_and_removed_on_drop() {
+        let first = write_temp_html("<p>first</p>").unwrap();
+        let second = write_temp_html("<p>second</p>").unwrap();
+        assert_ne!(first.path, second.path);
+        assert_eq!(fs::read_to_string(&first.path).unwrap(), "<p>first</p>");
+        assert_eq!(fs::read_to_string(&second.path).unwrap(), "<p>second</p>");
+
+        let paths = [first.path.clone(), second.path.clone()];
+        drop(first);
+        drop(second);
+        assert!(paths.iter().all(|p| !p.exists()));
+    }
+
+    #[test]
+    fn markdown_links_keep_their_href() {
+        let html = markdown_to_html("See [Rust](https://rust-lang.org).", 1);
+        assert!(html.contains(r#"<a href="https://rust-lang.org">Rust</a>"#));
+    }
+
+    #[test]
+    fn markdown_tables_render_as_html_tables() {
+        let markdown = "| Name | Type | Default |\n|---|---|---|\n| a | int | 0 |\n| b | str | \"\" |\n";
+        let html = markdown_to_html(markdown, 1);
+        assert_eq!(html.matches("<thead>").count(), 1);
+        assert_eq!(html.matches("<th>").count(), 3);
+        assert_eq!(html.matches("<td>").count(), 6);
+    }
+
+    #[test]
+    fn task_lists_and_strikethrough_render() {
+        let html = markdown_to_html("- [x] Read ~~chapter one~~\n- [ ] Do the quiz\n", 1);
+        assert_eq!(html.matches(r#"class="task-checkbox" checked disabled"#).count(), 1);
+        assert_eq!(html.matches(r#"class="task-checkbox" disabled"#).count(), 1);
+        assert!(html.contains("<del>chapter one</del>"));
+        assert!(!html.contains("[ ]"));
+    }
+
+    #[test]
+    fn nested_mixed_lists_are_well_formed() {
+        let html = markdown_to_html("1. First\n   - nested a\n   - nested b\n2. Second\n", 1);
+        assert_eq!(
+            html,
+            "<ol><li>First<ul><li>nested a</li><li>nested b</li></ul></li><li>Second</li></ol>"
+        );
+    }
+
+    #[test]
+    fn untagged_code_blocks_are_left_for_auto_detection() {
+        let html = markdown_to_html("```\nlet x = 1;\n```\n", 1);
+        assert!(html.contains("<pre><code>let x = 1;\n</code></pre>"));
+        assert!(!html.contains("language-plaintext"));
+
+        let html = markdown_to_html("```rust\nlet x = 1;\n```\n", 1);
+        assert!(html.contains(r#"<code class="language-rust">"#));
+    }
+
+    #[test]
+    fn footnote_references_and_definitions_link_to_each_other() {
+        let html = markdown_to_html("A claim.[^src]\n\n[^src]: The source.\n", 2);
+        assert!(html.contains(r##"<sup class="footnote-ref" id="fnref-2-1"><a href="#fn-2-1">1</a></sup>"##));
+        assert!(html.contains(r#"<li id="fn-2-1" value="1">"#));
+        assert!(html.contains("The source."));
+        assert!(html.contains(r##"<a href="#fnref-2-1" class="footnote-backref">"##));
+    }
+}
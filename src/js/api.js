@@ -21,6 +21,10 @@ export async function setBaseUrl(url) {
   return await invoke('set_base_url', { url });
 }
 
+export async function testConnection() {
+  return await invoke('test_connection');
+}
+
 export async function getModel() {
   return await invoke('get_model');
 }
@@ -37,28 +41,156 @@ export async function setProvider(provider) {
   return await invoke('set_provider', { provider });
 }
 
+export async function providerPresets() {
+  return await invoke('provider_presets');
+}
+
+export async function applyProviderPreset(id) {
+  return await invoke('apply_provider_preset', { id });
+}
+
+export async function setChatAutosave(intervalSecs, batchSize) {
+  return await invoke('set_chat_autosave', { intervalSecs, batchSize });
+}
+
+export async function setExtraParams(params) {
+  return await invoke('set_extra_params', { params });
+}
+
+export async function getContextLimit() {
+  return await invoke('get_context_limit');
+}
+
+export async function setContextLimit(tokens) {
+  return await invoke('set_context_limit', { tokens });
+}
+
+export async function setWebSearch(enabled) {
+  return await invoke('set_web_search', { enabled });
+}
+
+export async function setFallbackModel(model, afterFailures) {
+  return await invoke('set_fallback_model', { model, afterFailures });
+}
+
+export async function setMaxIterations(generation, chat) {
+  return await invoke('set_max_iterations', { generation, chat });
+}
+
+export async function setSamplingDefaults(maxTokens, topP) {
+  return await invoke('set_sampling_defaults', { maxTokens, topP });
+}
+
+export async function setTemperature(temperature) {
+  return await invoke('set_temperature', { temperature });
+}
+
+export async function setGenerationSeed(seed) {
+  return await invoke('set_generation_seed', { seed });
+}
+
+export async function setRequestTimeout(secs) {
+  return await invoke('set_request_timeout', { secs });
+}
+
+export async function setProxy(httpProxy, httpsProxy) {
+  return await invoke('set_proxy', { httpProxy, httpsProxy });
+}
+
 export async function getConfig() {
   return await invoke('get_config');
 }
 
+export async function getStoragePaths() {
+  return await invoke('get_storage_paths');
+}
+
+export async function getDataDir() {
+  return await invoke('get_data_dir');
+}
+
+export async function openDataDir() {
+  return await invoke('open_data_dir');
+}
+
 // Project commands
 export async function listProjects() {
   return await invoke('list_projects');
 }
 
+export async function listProjectsByTag(tag) {
+  return await invoke('list_projects_by_tag', { tag });
+}
+
 export async function getProject(id) {
   return await invoke('get_project', { id });
 }
 
+export async function getProjectStats(projectId) {
+  return await invoke('get_project_stats', { projectId });
+}
+
 export async function createProject(title, description) {
   return await invoke('create_project', { title, description });
 }
 
+export async function updateProject(projectId, title, description) {
+  return await invoke('update_project', { projectId, title, description });
+}
+
+export async function setProjectTags(projectId, tags) {
+  return await invoke('set_project_tags', { projectId, tags });
+}
+
 export async function deleteProject(id) {
   return await invoke('delete_project', { id });
 }
 
+export async function listTrashedProjects() {
+  return await invoke('list_trashed_projects');
+}
+
+export async function restoreProject(id) {
+  return await invoke('restore_project', { id });
+}
+
+export async function emptyTrash() {
+  return await invoke('empty_trash');
+}
+
+export async function duplicateProject(projectId) {
+  return await invoke('duplicate_project', { projectId });
+}
+
+export async function setProjectModel(projectId, model) {
+  return await invoke('set_project_model', { projectId, model });
+}
+
+export async function setProjectCover(projectId, cover) {
+  return await invoke('set_project_cover', { projectId, cover });
+}
+
+export async function setCustomInstructions(projectId, instructions) {
+  return await invoke('set_custom_instructions', { projectId, instructions });
+}
+
+export async function snapshotProject(projectId, name) {
+  return await invoke('snapshot_project', { projectId, name });
+}
+
+export async function listProjectRevisions(projectId) {
+  return await invoke('list_project_revisions', { projectId });
+}
+
+export async function restoreProjectRevision(projectId, name) {
+  return await invoke('restore_project_revision', { projectId, name });
+}
+
 // Page commands
+export async function getPage(projectId, pageName) {
+  return await invoke('get_page', { projectId, pageName });
+}
+
 export async function getPageContent(projectId, pageName) {
   return await invoke('get_page_content', { projectId, pageName });
 }
@@ -71,29 +203,145 @@ export async function addPage(projectId, title) {
   return await invoke('add_page', { projectId, title });
 }
 
+export async function insertPage(projectId, title, index) {
+  return await invoke('insert_page', { projectId, title, index });
+}
+
+export async function deletePage(projectId, pageName) {
+  return await invoke('delete_page', { projectId, pageName });
+}
+
+export async function renamePage(projectId, oldName, newTitle) {
+  return await invoke('rename_page', { projectId, oldName, newTitle });
+}
+
 export async function reorderPages(projectId, order) {
   return await invoke('reorder_pages', { projectId, order });
 }
 
-export async function importFolder(folderPath, title, description) {
-  return await invoke('import_folder', { folderPath, title, description });
+export async function mergePages(projectId, firstPage, secondPage, mergedTitle) {
+  return await invoke('merge_pages', { projectId, firstPage, secondPage, mergedTitle });
+}
+
+export async function splitPage(projectId, pageName, splitHeading) {
+  return await invoke('split_page', { projectId, pageName, splitHeading });
+}
+
+export async function linkCrossReferences(projectId) {
+  return await invoke('link_cross_references', { projectId });
+}
+
+export async function listPageVersions(projectId, pageName) {
+  return await invoke('list_page_versions', { projectId, pageName });
+}
+
+export async function restorePageVersion(projectId, pageName, timestamp) {
+  return await invoke('restore_page_version', { projectId, pageName, timestamp });
+}
+
+export async function listAnnotations(projectId, pageName = null) {
+  return await invoke('list_annotations', { projectId, pageName });
+}
+
+export async function addAnnotation(projectId, pageName, line, text) {
+  return await invoke('add_annotation', { projectId, pageName, line, text });
+}
+
+export async function deleteAnnotation(projectId, annotationId) {
+  return await invoke('delete_annotation', { projectId, annotationId });
+}
+
+export async function importFolder(folderPath, title, description, recursive = false) {
+  return await invoke('import_folder', { folderPath, title, description, recursive });
+}
+
+export async function appendFolderToProject(projectId, folderPath, recursive = false) {
+  return await invoke('append_folder_to_project', { projectId, folderPath, recursive });
+}
+
+export async function importMarkdownFile(projectId, filePath) {
+  return await invoke('import_markdown_file', { projectId, filePath });
 }
 
 // AI commands (stubs - you will implement these)
-export async function generateLearning(topic, depth) {
-  return await invoke('generate_learning', { topic, depth });
+export async function generateLearning(topic, depth, planThenWrite = false, language = null, targetWordsPerChapter = null) {
+  return await invoke('generate_learning', { topic, depth, planThenWrite, language, targetWordsPerChapter });
+}
+
+export async function generateFromObjectives(topic, objectives, depth) {
+  return await invoke('generate_from_objectives', { topic, objectives, depth });
+}
+
+export async function resumeGeneration(projectId) {
+  return await invoke('resume_generation', { projectId });
+}
+
+export async function cancelGeneration(projectId) {
+  return await invoke('cancel_generation', { projectId });
+}
+
+export async function regeneratePage(projectId, pageName, instructions) {
+  return await invoke('regenerate_page', { projectId, pageName, instructions });
+}
+
+export async function continuePage(projectId, pageName, guidance = '') {
+  return await invoke('continue_page', { projectId, pageName, guidance });
+}
+
+export async function generateQuiz(projectId, pageName, numQuestions = 5) {
+  return await invoke('generate_quiz', { projectId, pageName, numQuestions });
+}
+
+export async function generateGlossary(projectId) {
+  return await invoke('generate_glossary', { projectId });
+}
+
+export async function verifyObjectivesCovered(projectId) {
+  return await invoke('verify_objectives_covered', { projectId });
+}
+
+export async function assessCompleteness(projectId) {
+  return await invoke('assess_completeness', { projectId });
 }
 
-export async function expandSelection(projectId, pageName, selection, question) {
-  return await invoke('expand_selection', { projectId, pageName, selection, question });
+export async function generateSeries(seriesTitle, topics, depth) {
+  return await invoke('generate_series', { seriesTitle, topics, depth });
+}
+
+export async function expandSelection(projectId, pageName, selection, question, apply = true) {
+  return await invoke('expand_selection', { projectId, pageName, selection, question, apply });
+}
+
+export async function previewExpansion(projectId, pageName, selection, question) {
+  return await invoke('preview_expansion', { projectId, pageName, selection, question });
+}
+
+export async function acceptExpansion(expansionId) {
+  return await invoke('accept_expansion', { expansionId });
+}
+
+export async function rejectExpansion(expansionId) {
+  return await invoke('reject_expansion', { expansionId });
+}
+
+export async function estimateExpansionContext(projectId, pageName, selection, question) {
+  return await invoke('estimate_expansion_context', { projectId, pageName, selection, question });
 }
 
 export async function removeExpansion(projectId, pageName, expansionId) {
   return await invoke('remove_expansion', { projectId, pageName, expansionId });
 }
 
-export async function answerQuestion(selection, question) {
-  return await invoke('answer_question', { selection, question });
+export async function undoLastExpansion(projectId, pageName) {
+  return await invoke('undo_last_expansion', { projectId, pageName });
+}
+
+export async function listExpansions(projectId) {
+  return await invoke('list_expansions', { projectId });
+}
+
+export async function answerQuestion(selection, question, projectId = null, pageName = null) {
+  return await invoke('answer_question', { selection, question, projectId, pageName });
 }
 
 // Chat commands
@@ -109,6 +357,18 @@ export async function getChatSession(projectId, sessionId) {
   return await invoke('get_chat_session', { projectId, sessionId });
 }
 
+export async function getSessionActions(projectId, sessionId) {
+  return await invoke('get_session_actions', { projectId, sessionId });
+}
+
+export async function revertChatChanges(projectId, sessionId) {
+  return await invoke('revert_chat_changes', { projectId, sessionId });
+}
+
+export async function closeChatSession(projectId, sessionId) {
+  return await invoke('close_chat_session', { projectId, sessionId });
+}
+
 export async function deleteChatSession(projectId, sessionId) {
   return await invoke('delete_chat_session', { projectId, sessionId });
 }
@@ -117,11 +377,44 @@ export async function sendChatMessage(projectId, sessionId, message) {
   return await invoke('send_chat_message', { projectId, sessionId, message });
 }
 
+export async function cancelChat(sessionId) {
+  return await invoke('cancel_chat', { sessionId });
+}
+
 // Export commands
-export async function exportToPdf(projectId, outputPath) {
-  return await invoke('export_to_pdf', { projectId, outputPath });
+export async function exportToPdf(projectId, outputPath, options = null, pageNames = null) {
+  return await invoke('export_to_pdf', { projectId, outputPath, options, pageNames });
+}
+
+export async function exportToHtml(projectId, outputPath) {
+  return await invoke('export_to_html', { projectId, outputPath });
+}
+
+export async function exportToMarkdown(projectId, outputPath) {
+  return await invoke('export_to_markdown', { projectId, outputPath });
 }
 
 export async function getExportsDir() {
   return await invoke('get_exports_dir');
 }
+
+export async function cleanupExportTempFiles() {
+  return await invoke('cleanup_export_temp_files');
+}
+
+// Backup commands
+export async function createBackup(outputPath, includeApiKey = false) {
+  return await invoke('create_backup', { outputPath, includeApiKey });
+}
+
+export async function restoreBackup(backupPath) {
+  return await invoke('restore_backup', { backupPath });
+}
+
+export async function exportProjectBundle(projectId, outputPath) {
+  return await invoke('export_project_bundle', { projectId, outputPath });
+}
+
+export async function importProjectBundle(zipPath) {
+  return await invoke('import_project_bundle', { zipPath });
+}
@@ -30,8 +30,8 @@ const PROVIDERS = {
     name: 'Anthropic',
     baseUrl: 'https://api.anthropic.com/v1',
     model: 'claude-sonnet-4-20250514',
-    available: false,
-    description: 'Not available - use OpenRouter for Claude models'
+    available: true,
+    description: 'Claude models via the Anthropic Messages API'
   },
   google: {
     name: 'Google AI',
@@ -67,7 +67,7 @@ export async function renderSettings() {
         <!-- Provider Configuration -->
         <div class="card">
           <h3 class="card-title">AI Provider</h3>
-          <p class="card-description">Configure your OpenAI-compatible API provider.</p>
+          <p class="card-description">Configure your AI provider (OpenAI-compatible or Anthropic).</p>
 
           <div class="form-group" style="margin-top: 16px;">
             <label class="form-label">Provider</label>